@@ -672,6 +672,7 @@ fn main() {
         .into_iter()
         .enumerate()
         .flat_map(|(_, f)| f)
+        .chain(red_zones())
         .collect::<Vec<_>>();
     let height = flat_fields
         .iter()
@@ -690,6 +691,18 @@ fn main() {
     file.write(json.as_bytes()).unwrap();
 }
 
+/// The positions above the ghost house and above capmans spawn, where ghosts cannot turn up in chase and scatter.
+/// They share their positions with other elements, so they are not part of the lines above.
+fn red_zones() -> Vec<Field> {
+    [(14, 11), (17, 11), (14, 23), (17, 23)]
+        .into_iter()
+        .map(|(x, y)| Field {
+            position: Position::new(x, y),
+            element: RedZone,
+        })
+        .collect()
+}
+
 #[derive(Copy, Clone)]
 enum QuickWall {
     I,
@@ -7,11 +7,15 @@ pub struct AnimationPlugin;
 impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_system(update_entities_with_animations)
+            .add_system(update_entities_with_animations.label(LAnimationUpdate))
         ;
     }
 }
 
+/// Marks the system that advances every animation. Systems checking if an animation finished run after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct LAnimationUpdate;
+
 fn update_entities_with_animations(
     time: Res<Time>,
     mut query: Query<(&Visibility, &mut Handle<Image>, &mut Animations)>,
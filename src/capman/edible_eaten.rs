@@ -1,5 +1,5 @@
 use crate::capman::Capman;
-use crate::interactions::{EDotEaten, EEnergizerEaten};
+use crate::interactions::{EDotEaten, EEnergizerEaten, LCapmanInteractions};
 use crate::life_cycle::LifeCycle::Running;
 use bevy::prelude::*;
 use std::time::Duration;
@@ -14,7 +14,8 @@ impl Plugin for EdibleEatenPlugin {
             SystemSet::on_update(Running)
                 .with_system(add_edible_stop_when_dot_eaten)
                 .with_system(add_edible_stop_when_energizer_eaten)
-                .with_system(remove_edible_stop_when_timer_ended),
+                .with_system(remove_edible_stop_when_timer_ended)
+                .after(LCapmanInteractions),
         );
     }
 }
@@ -24,8 +25,8 @@ fn add_edible_stop_when_dot_eaten(
     mut event_reader: EventReader<EDotEaten>,
     query: Query<Entity, With<Capman>>,
 ) {
-    for _ in event_reader.iter() {
-        for e in &query {
+    for event in event_reader.iter() {
+        if let Ok(e) = query.get(**event) {
             commands.entity(e).insert(EdibleEatenStop(Timer::new(
                 Duration::from_secs_f32(1.0 / 60.0),
                 false,
@@ -39,8 +40,8 @@ fn add_edible_stop_when_energizer_eaten(
     mut event_reader: EventReader<EEnergizerEaten>,
     query: Query<Entity, With<Capman>>,
 ) {
-    for _ in event_reader.iter() {
-        for e in &query {
+    for event in event_reader.iter() {
+        if let Ok(e) = query.get(**event) {
             commands.entity(e).insert(EdibleEatenStop(Timer::new(
                 Duration::from_secs_f32(3.0 / 60.0),
                 false,
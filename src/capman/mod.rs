@@ -1,22 +1,46 @@
-use crate::animation::Animations;
+use crate::animation::{Animations, LAnimationUpdate};
 use crate::game_assets::loaded_assets::LoadedAssets;
 use bevy::prelude::*;
+use bevy::utils::HashSet;
 
+use crate::board_dimensions::BoardDimensions;
 use crate::capman::edible_eaten::EdibleEatenPlugin;
-use crate::capman::movement::{move_capman, set_direction_based_on_keyboard_input, InputBuffer};
-use crate::capman::spawn::spawn_capman;
+use crate::capman::movement::{move_capman, set_direction_based_on_player_input};
+use crate::capman::spawn::{capman_spawn_transform, spawn_capman};
 use crate::capman::textures::{start_animation, update_capman_appearance};
+use crate::common::Direction;
+use crate::interactions::{ECapmanHit, LCapmanInteractions};
 use crate::life_cycle::LifeCycle::*;
+use crate::lives::Life;
+use crate::map::Map;
+use crate::players::input::InputBuffer;
+use crate::players::Player;
 
 mod edible_eaten;
 mod movement;
 mod spawn;
 mod textures;
 
+/// Marks the system that moves capman. Everything capman touches is checked after he moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct LCapmanMovement;
+
+/// Marks the system that checks if the dying capmen finished their animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct LCapmanDeathCheck;
+
 /// Marker component for a capman entity.
 #[derive(Component)]
 pub struct Capman;
 
+/// Marks a dying capman.
+///
+/// A capman that was hit while other capmen keep playing dies alone. He does not move or eat and cannot be
+/// hit again. After his dying animation he respawns if his player has lives left, otherwise he is removed
+/// from the maze. When the game as a whole lost a life, every capman gets marked while the game is dying.
+#[derive(Component, Debug)]
+pub struct Dying;
+
 /// Fired when capman died.
 pub struct ECapmanDead;
 
@@ -26,16 +50,29 @@ impl Plugin for CapmanPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ECapmanDead>()
             .add_plugin(EdibleEatenPlugin)
-            .insert_resource(InputBuffer(None))
             .add_system_set(SystemSet::on_enter(Ready).with_system(spawn_capman))
             .add_system_set(SystemSet::on_enter(Running).with_system(start_animation))
             .add_system_set(
                 SystemSet::on_update(Running)
-                    .with_system(move_capman)
-                    .with_system(set_direction_based_on_keyboard_input)
+                    .with_system(respawn_or_remove_capman_when_died_alone.after(LAnimationUpdate))
+                    .with_system(
+                        set_direction_based_on_player_input
+                            .after(respawn_or_remove_capman_when_died_alone),
+                    )
                     .with_system(
-                        update_capman_appearance.after(set_direction_based_on_keyboard_input),
-                    ),
+                        move_capman
+                            .label(LCapmanMovement)
+                            .after(set_direction_based_on_player_input),
+                    )
+                    .with_system(
+                        update_capman_appearance.after(set_direction_based_on_player_input),
+                    )
+                    .with_system(
+                        let_hit_capman_die_alone_when_others_keep_playing
+                            .after(LCapmanInteractions)
+                            .after(LAnimationUpdate),
+                    )
+                    .with_system(play_the_dying_sound_of_capmen_dying_alone),
             )
             .add_system_set(SystemSet::on_enter(CapmanHit).with_system(stop_animation))
             .add_system_set(
@@ -44,7 +81,11 @@ impl Plugin for CapmanPlugin {
                     .with_system(play_the_dying_sound),
             )
             .add_system_set(
-                SystemSet::on_update(CapmanDying).with_system(check_if_capman_finished_dying),
+                SystemSet::on_update(CapmanDying).with_system(
+                    check_if_capman_finished_dying
+                        .label(LCapmanDeathCheck)
+                        .after(LAnimationUpdate),
+                ),
             )
             .add_system_set(SystemSet::on_enter(CapmanDead).with_system(despawn_capman))
             .add_system_set(SystemSet::on_enter(LevelTransition).with_system(stop_animation))
@@ -54,14 +95,96 @@ impl Plugin for CapmanPlugin {
     }
 }
 
+/// Return true if every capman that is still playing was hit, so the game as a whole loses a life.
+///
+/// If at least one capman keeps playing, only the hit ones die while the game continues.
+pub fn all_playing_capmen_hit(
+    hit_capmen: &HashSet<Entity>,
+    playing_capmen: impl IntoIterator<Item = Entity>,
+) -> bool {
+    !hit_capmen.is_empty()
+        && playing_capmen
+            .into_iter()
+            .all(|capman| hit_capmen.contains(&capman))
+}
+
+fn let_hit_capman_die_alone_when_others_keep_playing(
+    mut commands: Commands,
+    mut event_reader: EventReader<ECapmanHit>,
+    mut query: Query<(Entity, &mut Animations), (With<Capman>, Without<Dying>)>,
+) {
+    let hit_capmen = event_reader.iter().map(|e| **e).collect::<HashSet<_>>();
+
+    if hit_capmen.is_empty() || all_playing_capmen_hit(&hit_capmen, query.iter().map(|(e, _)| e)) {
+        return;
+    }
+
+    for (entity, mut animations) in &mut query {
+        if hit_capmen.contains(&entity) {
+            commands.entity(entity).insert(Dying);
+            animations.change_animation_to("dying");
+        }
+    }
+}
+
+fn play_the_dying_sound_of_capmen_dying_alone(
+    audio: Res<Audio>,
+    loaded_assets: Res<LoadedAssets>,
+    query: Query<(), (With<Capman>, Added<Dying>)>,
+) {
+    for _ in &query {
+        audio.play(loaded_assets.get_handle("sounds/dying.ogg"));
+    }
+}
+
+/// When a capman finished dying on his own, he gets back to his spawn if his player has
+/// lives left. Otherwise, he is removed and the other players continue without him.
+fn respawn_or_remove_capman_when_died_alone(
+    mut commands: Commands,
+    map: Res<Map>,
+    dimensions: Res<BoardDimensions>,
+    life_query: Query<&Player, With<Life>>,
+    mut query: Query<
+        (
+            Entity,
+            &Player,
+            &mut Transform,
+            &mut Direction,
+            &mut InputBuffer,
+            &mut Animations,
+        ),
+        (With<Capman>, With<Dying>),
+    >,
+) {
+    for (entity, player, mut transform, mut direction, mut input_buffer, mut animations) in &mut query {
+        if !animations.current().is_completely_finished() {
+            continue;
+        }
+
+        if life_query.iter().any(|p| p == player) {
+            *transform = capman_spawn_transform(&map, &dimensions);
+            *direction = Direction::Up;
+            **input_buffer = None;
+            animations.change_animation_to("eating_up");
+            commands.entity(entity).remove::<Dying>();
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
 fn stop_animation(mut query: Query<&mut Animations, With<Capman>>) {
     for mut animations in query.iter_mut() {
         animations.stop()
     }
 }
 
-fn play_the_dying_animation(mut query: Query<&mut Animations, With<Capman>>) {
-    for mut animations in query.iter_mut() {
+fn play_the_dying_animation(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Animations), (With<Capman>, Without<Dying>)>,
+) {
+    for (entity, mut animations) in query.iter_mut() {
+        commands.entity(entity).insert(Dying);
         animations.resume();
         animations.change_animation_to("dying")
     }
@@ -71,14 +194,17 @@ fn play_the_dying_sound(audio: Res<Audio>, loaded_assets: Res<LoadedAssets>) {
     audio.play(loaded_assets.get_handle("sounds/dying.ogg"));
 }
 
+/// In co-op, every capman dies at the same time, so the game waits until the last one finished.
 fn check_if_capman_finished_dying(
     mut event_writer: EventWriter<ECapmanDead>,
     query: Query<&Animations, With<Capman>>,
 ) {
-    for animations in query.iter() {
-        if animations.current().is_completely_finished() {
-            event_writer.send(ECapmanDead)
-        }
+    if !query.is_empty()
+        && query
+            .iter()
+            .all(|animations| animations.current().is_completely_finished())
+    {
+        event_writer.send(ECapmanDead)
     }
 }
 
@@ -99,3 +225,95 @@ fn set_visible(mut query: Query<&mut Visibility, With<Capman>>) {
         vis.is_visible = true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use bevy::utils::HashSet;
+
+    use crate::animation::{Animation, Animations};
+    use crate::capman::{
+        all_playing_capmen_hit, let_hit_capman_die_alone_when_others_keep_playing, Capman, Dying,
+    };
+    use crate::interactions::ECapmanHit;
+
+    fn entities(n: u32) -> Vec<Entity> {
+        (0..n).map(Entity::from_raw).collect()
+    }
+
+    #[test]
+    fn the_game_loses_a_life_when_every_playing_capman_was_hit() {
+        let capmen = entities(2);
+        let every_capman = capmen.iter().copied().collect();
+        let first_capman = HashSet::from_iter([capmen[0]]);
+
+        assert!(all_playing_capmen_hit(&every_capman, capmen.clone()));
+        assert!(!all_playing_capmen_hit(&first_capman, capmen.clone()));
+        assert!(!all_playing_capmen_hit(&HashSet::default(), capmen));
+    }
+
+    #[test]
+    fn capmen_that_are_no_longer_playing_do_not_count() {
+        let capmen = entities(2);
+        let first_capman = HashSet::from_iter([capmen[0]]);
+
+        assert!(all_playing_capmen_hit(&first_capman, [capmen[0]]));
+    }
+
+    fn spawn_capman(app: &mut App) -> Entity {
+        let animations = Animations::new(
+            [
+                ("eating_up", Animation::from_texture(Handle::default())),
+                ("dying", Animation::from_texture(Handle::default())),
+            ],
+            "eating_up",
+        );
+        app.world.spawn().insert(Capman).insert(animations).id()
+    }
+
+    fn app_with_capmen(n: usize) -> (App, Vec<Entity>) {
+        let mut app = App::new();
+        app.add_event::<ECapmanHit>()
+            .add_system(let_hit_capman_die_alone_when_others_keep_playing);
+        let capmen = (0..n).map(|_| spawn_capman(&mut app)).collect();
+        (app, capmen)
+    }
+
+    fn is_dying(app: &App, capman: Entity) -> bool {
+        app.world.get::<Dying>(capman).is_some()
+    }
+
+    #[test]
+    fn a_hit_capman_dies_alone_while_others_keep_playing() {
+        let (mut app, capmen) = app_with_capmen(2);
+
+        app.world.send_event(ECapmanHit(capmen[0]));
+        app.update();
+
+        assert!(is_dying(&app, capmen[0]));
+        assert!(!is_dying(&app, capmen[1]));
+    }
+
+    #[test]
+    fn nobody_dies_alone_when_every_capman_was_hit() {
+        let (mut app, capmen) = app_with_capmen(2);
+
+        app.world.send_event(ECapmanHit(capmen[0]));
+        app.world.send_event(ECapmanHit(capmen[1]));
+        app.update();
+
+        assert!(!is_dying(&app, capmen[0]));
+        assert!(!is_dying(&app, capmen[1]));
+    }
+
+    #[test]
+    fn the_last_playing_capman_does_not_die_alone() {
+        let (mut app, capmen) = app_with_capmen(2);
+        app.world.entity_mut(capmen[1]).insert(Dying);
+
+        app.world.send_event(ECapmanHit(capmen[0]));
+        app.update();
+
+        assert!(!is_dying(&app, capmen[0]));
+    }
+}
@@ -3,12 +3,14 @@ use bevy::ecs::query::WorldQuery;
 use bevy::prelude::*;
 
 use crate::capman::edible_eaten::EdibleEatenStop;
-use crate::capman::Capman;
+use crate::capman::{Capman, Dying};
 use crate::common::position::Position;
 use crate::common::Direction;
 use crate::common::Direction::*;
 use crate::constants::CAPMAN_Z;
 use crate::map::board::Board;
+use crate::players::input::{get_wished_direction, InputBuffer, PlayerInputs};
+use crate::players::Player;
 use crate::speed::Speed;
 
 #[derive(WorldQuery)]
@@ -23,7 +25,7 @@ pub(in crate::capman) fn move_capman(
     board: Res<Board>,
     time: Res<Time>,
     dimensions: Res<BoardDimensions>,
-    mut query: Query<MoveComponents, (With<Capman>, Without<EdibleEatenStop>)>,
+    mut query: Query<MoveComponents, (With<Capman>, Without<EdibleEatenStop>, Without<Dying>)>,
 ) {
     let delta_seconds = time.delta_seconds();
 
@@ -130,16 +132,18 @@ fn center_position(
     }
 }
 
-pub(in crate::capman) fn set_direction_based_on_keyboard_input(
+pub(in crate::capman) fn set_direction_based_on_player_input(
     board: Res<Board>,
     dimensions: Res<BoardDimensions>,
-    keyboard_input: Res<Input<KeyCode>>,
-    mut input_buffer: ResMut<InputBuffer>,
-    mut query: Query<(&Transform, &mut Direction), With<Capman>>,
+    player_inputs: Res<PlayerInputs>,
+    mut query: Query<
+        (&Transform, &mut Direction, &Player, &mut InputBuffer),
+        (With<Capman>, Without<Dying>),
+    >,
 ) {
-    for (transform, mut direction) in query.iter_mut() {
+    for (transform, mut direction, player, mut input_buffer) in query.iter_mut() {
         let position = dimensions.vec_to_pos(&transform.translation);
-        let wished_direction = get_wished_direction(&keyboard_input, &input_buffer);
+        let wished_direction = get_wished_direction(&player_inputs, player, &input_buffer);
 
         if let Some(dir) = wished_direction {
             let position_in_direction = position.neighbour_position(&dir);
@@ -161,30 +165,6 @@ pub(in crate::capman) fn set_direction_based_on_keyboard_input(
     }
 }
 
-/// Return the direction capman should move to next. If no matching keyboard key was pressed, return the last buffered input.
-fn get_wished_direction(
-    keyboard_input: &Input<KeyCode>,
-    input_buffer: &InputBuffer,
-) -> Option<Direction> {
-    if keyboard_input.pressed(KeyCode::Left) || keyboard_input.pressed(KeyCode::A) {
-        return Some(Left);
-    }
-
-    if keyboard_input.pressed(KeyCode::Right) || keyboard_input.pressed(KeyCode::D) {
-        return Some(Right);
-    }
-
-    if keyboard_input.pressed(KeyCode::Up) || keyboard_input.pressed(KeyCode::W) {
-        return Some(Up);
-    }
-
-    if keyboard_input.pressed(KeyCode::Down) || keyboard_input.pressed(KeyCode::S) {
-        return Some(Down);
-    }
-
-    **input_buffer
-}
-
 fn is_centered_enough(
     coordinates: Vec3,
     direction: Direction,
@@ -200,7 +180,3 @@ fn is_centered_enough(
         Left | Right => y >= posy - max_distance && y <= posy + max_distance,
     }
 }
-
-/// Saves the wished direction capman should move to next.
-#[derive(Deref, DerefMut)]
-pub struct InputBuffer(pub Option<Direction>);
@@ -8,8 +8,11 @@ use crate::constants::CAPMAN_Z;
 use crate::game_assets::loaded_assets::LoadedAssets;
 use crate::is;
 use crate::level::Level;
+use crate::lives::Life;
 use crate::map::Element::CapManSpawn;
 use crate::map::Map;
+use crate::players::input::{InputBuffer, KeyBindings};
+use crate::players::{GameMode, Player};
 use crate::specs_per_level::SpecsPerLevel;
 use crate::speed::Speed;
 use crate::sprite_sheet::SpriteSheet;
@@ -18,38 +21,55 @@ use crate::sprite_sheet::SpriteSheet;
 #[derive(Deref, DerefMut)]
 pub struct CapmanSpawn(Vec3);
 
+/// Spawn a capman for every player that has lives left.
 pub(in crate::capman) fn spawn_capman(
     mut commands: Commands,
     game_assets: Res<LoadedAssets>,
     sprite_sheets: Res<Assets<SpriteSheet>>,
     map: Res<Map>,
     level: Res<Level>,
+    game_mode: Res<GameMode>,
     specs_per_level: Res<SpecsPerLevel>,
     dimensions: Res<BoardDimensions>,
+    life_query: Query<&Player, With<Life>>,
 ) {
-    let transform =
-        dimensions.positions_to_trans(map.get_positions_matching(is!(CapManSpawn)), CAPMAN_Z);
-    let dimension = Vec2::new(dimensions.capman(), dimensions.capman());
+    for player in game_mode.capman_players() {
+        if life_query.iter().all(|p| p != &player) {
+            continue;
+        }
 
-    let mut animations = create_capman_animations(&game_assets, &sprite_sheets);
-    animations.stop();
+        let transform = capman_spawn_transform(&map, &dimensions);
+        let dimension = Vec2::new(dimensions.capman(), dimensions.capman());
 
-    commands
-        .spawn()
-        .insert_bundle(SpriteBundle {
-            texture: animations.current().texture(),
-            sprite: Sprite {
-                custom_size: Some(dimension),
-                ..default()
-            },
-            transform,
-            ..Default::default()
-        })
-        .insert(Capman)
-        .insert(Speed(
-            dimensions.capman_base_speed()
-                * specs_per_level.get_for(&level).capman_normal_speed_modifier,
-        ))
-        .insert(Up)
-        .insert(animations);
+        let mut animations = create_capman_animations(&game_assets, &sprite_sheets);
+        animations.stop();
+
+        commands
+            .spawn()
+            .insert_bundle(SpriteBundle {
+                texture: animations.current().texture(),
+                sprite: Sprite {
+                    color: player.tint(),
+                    custom_size: Some(dimension),
+                    ..default()
+                },
+                transform,
+                ..Default::default()
+            })
+            .insert(Capman)
+            .insert(player)
+            .insert(KeyBindings::for_player(player, &game_mode))
+            .insert(InputBuffer::default())
+            .insert(Speed(
+                dimensions.capman_base_speed()
+                    * specs_per_level.get_for(&level).capman_normal_speed_modifier,
+            ))
+            .insert(Up)
+            .insert(animations);
+    }
+}
+
+/// Return the transform of the capman spawn, which lies between two fields on the map.
+pub(in crate::capman) fn capman_spawn_transform(map: &Map, dimensions: &BoardDimensions) -> Transform {
+    dimensions.positions_to_trans(map.get_positions_matching(is!(CapManSpawn)), CAPMAN_Z)
 }
@@ -1,14 +1,14 @@
 use crate::animation::{Animation, Animations};
 use bevy::prelude::*;
 
-use crate::capman::Capman;
+use crate::capman::{Capman, Dying};
 use crate::common::Direction;
 use crate::common::Direction::*;
 use crate::game_assets::loaded_assets::LoadedAssets;
 use crate::sprite_sheet::SpriteSheet;
 
 pub(in crate::capman) fn update_capman_appearance(
-    mut query: Query<(&Direction, &mut Animations), With<Capman>>,
+    mut query: Query<(&Direction, &mut Animations), (With<Capman>, Without<Dying>)>,
 ) {
     for (direction, mut animations) in query.iter_mut() {
         match direction {
@@ -0,0 +1,190 @@
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::time::TimeSystem;
+
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(CoreStage::First, apply_fixed_tick.after(TimeSystem))
+            .add_system_to_stage(CoreStage::First, apply_time_scale.after(apply_fixed_tick));
+    }
+}
+
+/// If this resource exists, every frame advances the game by exactly this duration, no matter how long the
+/// frame really took.
+///
+/// Networked games need this, because every instance must run the same simulation step by step. A fixed tick also
+/// makes headless tests independent of the speed of the machine they run on.
+#[derive(Copy, Clone, Deref)]
+pub struct FixedTick(pub Duration);
+
+impl FixedTick {
+    pub fn per_second(ticks: u32) -> Self {
+        FixedTick(Duration::from_secs(1) / ticks)
+    }
+}
+
+/// Overwrite the measured time with a time that advanced by one fixed tick.
+///
+/// The fixed time is kept separately, because bevy updates the real time every frame before this system runs.
+fn apply_fixed_tick(
+    fixed_tick: Option<Res<FixedTick>>,
+    mut time: ResMut<Time>,
+    mut fixed_time: Local<Option<(Time, Instant)>>,
+) {
+    let fixed_tick = match fixed_tick {
+        Some(tick) => **tick,
+        None => return,
+    };
+
+    let (fixed_time, instant) = fixed_time.get_or_insert_with(|| {
+        let mut time = Time::default();
+        let startup = time.startup();
+        time.update_with_instant(startup);
+        (time, startup)
+    });
+    *instant += fixed_tick;
+    fixed_time.update_with_instant(*instant);
+    *time = fixed_time.clone();
+}
+
+/// If this resource exists, the game runs with the given factor of its normal speed (0.5 is half as fast). The game
+/// can also be paused and then advanced frame by frame.
+///
+/// Like the fixed tick, the scale is applied to the time itself, so every system using the time (timers, animations,
+/// movement) slows down, speeds up or stops the same way.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimeScale {
+    scale: f32,
+    paused: bool,
+    steps: usize,
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale::new(1.0)
+    }
+}
+
+impl TimeScale {
+    pub fn new(scale: f32) -> Self {
+        TimeScale {
+            scale,
+            paused: false,
+            steps: 0,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.steps = 0
+    }
+
+    /// Let one more frame pass while paused. Does nothing if the game is not paused.
+    pub fn step(&mut self) {
+        if self.paused {
+            self.steps += 1
+        }
+    }
+
+    /// Return how much time passes in the game in a frame that really took the given time.
+    fn scaled_delta(&mut self, delta: Duration) -> Duration {
+        if self.paused {
+            if self.steps == 0 {
+                return Duration::ZERO;
+            }
+            self.steps -= 1
+        }
+
+        // an unscaled time keeps networked games, which compare their time, exactly in sync
+        if self.scale == 1.0 {
+            delta
+        } else {
+            delta.mul_f64(self.scale as f64)
+        }
+    }
+}
+
+/// Overwrite the measured time with a time that advanced by the scaled delta.
+fn apply_time_scale(
+    time_scale: Option<ResMut<TimeScale>>,
+    mut time: ResMut<Time>,
+    mut scaled_time: Local<Option<(Time, Instant)>>,
+) {
+    let mut time_scale = match time_scale {
+        Some(scale) => scale,
+        None => return,
+    };
+
+    let (scaled_time, instant) = scaled_time.get_or_insert_with(|| {
+        let mut scaled_time = Time::default();
+        let startup = scaled_time.startup();
+        scaled_time.update_with_instant(startup);
+        (scaled_time, startup)
+    });
+    *instant += time_scale.scaled_delta(time.delta());
+    scaled_time.update_with_instant(*instant);
+    *time = scaled_time.clone();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::clock::TimeScale;
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    #[test]
+    fn the_scale_multiplies_the_delta() {
+        assert_eq!(TimeScale::new(1.0).scaled_delta(FRAME), FRAME);
+        assert_eq!(TimeScale::new(0.5).scaled_delta(FRAME), Duration::from_millis(8));
+        assert_eq!(TimeScale::new(2.0).scaled_delta(FRAME), Duration::from_millis(32));
+    }
+
+    #[test]
+    fn no_time_passes_while_paused() {
+        let mut time_scale = TimeScale::new(1.0);
+        time_scale.toggle_pause();
+
+        assert_eq!(time_scale.scaled_delta(FRAME), Duration::ZERO);
+
+        time_scale.toggle_pause();
+        assert_eq!(time_scale.scaled_delta(FRAME), FRAME);
+    }
+
+    #[test]
+    fn every_step_lets_one_scaled_frame_pass() {
+        let mut time_scale = TimeScale::new(0.5);
+        time_scale.toggle_pause();
+        time_scale.step();
+        time_scale.step();
+
+        assert_eq!(time_scale.scaled_delta(FRAME), Duration::from_millis(8));
+        assert_eq!(time_scale.scaled_delta(FRAME), Duration::from_millis(8));
+        assert_eq!(time_scale.scaled_delta(FRAME), Duration::ZERO);
+    }
+
+    #[test]
+    fn steps_are_ignored_when_not_paused() {
+        let mut time_scale = TimeScale::new(1.0);
+        time_scale.step();
+        time_scale.toggle_pause();
+
+        assert_eq!(time_scale.scaled_delta(FRAME), Duration::ZERO);
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::edibles::fruit::Fruit;
+use crate::ghosts::state::State;
+use crate::ghosts::state::State::*;
+
+/// A command typed into the console or read from a script.
+#[derive(Clone, Debug, PartialEq)]
+pub(in crate::debug) enum ConsoleCommand {
+    /// Jump to the given level.
+    Level(usize),
+    /// Give every capman player exactly this many lives.
+    Lives(usize),
+    /// Set the score of every capman player.
+    Score(usize),
+    /// Toggle whether capman ignores hits by ghosts.
+    God,
+    /// Toggle whether the ghosts stand still.
+    FreezeGhosts,
+    SpawnFruit(Fruit),
+    /// Force every ghost into the given state, or remove every forced state with None.
+    State(Option<State>),
+    /// Reseed the random number generator.
+    Seed(u64),
+    /// Start the current level again on the map with the given name.
+    Map(String),
+    /// Let the game run with the given factor of its normal speed.
+    Speed(f32),
+    /// Start recording every following command into a script. Record, save and run are never recorded.
+    Record,
+    /// Save the recorded script to the given file and stop recording.
+    Save(String),
+    /// Run every command of the given script file.
+    Run(String),
+}
+
+impl FromStr for ConsoleCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+
+        match words.as_slice() {
+            ["level", level] => match parse_number(level)? {
+                0 => Err("levels start with 1".to_string()),
+                level => Ok(ConsoleCommand::Level(level)),
+            },
+            ["lives", lives] => Ok(ConsoleCommand::Lives(parse_number(lives)?)),
+            ["score", score] => Ok(ConsoleCommand::Score(parse_number(score)?)),
+            ["god"] => Ok(ConsoleCommand::God),
+            ["freeze", "ghosts"] => Ok(ConsoleCommand::FreezeGhosts),
+            ["spawn", "fruit", fruit] => Ok(ConsoleCommand::SpawnFruit(fruit.parse()?)),
+            ["state", "clear"] => Ok(ConsoleCommand::State(None)),
+            ["state", state] => Ok(ConsoleCommand::State(Some(parse_state(state)?))),
+            ["seed", seed] => Ok(ConsoleCommand::Seed(parse_number(seed)?)),
+            ["map", name] => Ok(ConsoleCommand::Map(name.to_string())),
+            ["speed", speed] => match speed.parse::<f32>() {
+                Ok(speed) if speed > 0.0 => Ok(ConsoleCommand::Speed(speed)),
+                _ => Err(format!("'{}' is no valid speed", speed)),
+            },
+            ["record"] => Ok(ConsoleCommand::Record),
+            ["save", file] => Ok(ConsoleCommand::Save(file.to_string())),
+            ["run", file] => Ok(ConsoleCommand::Run(file.to_string())),
+            [] => Err("no command given".to_string()),
+            _ => Err(format!("unknown command '{}'", s.trim())),
+        }
+    }
+}
+
+fn parse_number<T: FromStr>(word: &str) -> Result<T, String> {
+    word.parse()
+        .map_err(|_| format!("'{}' is no valid number", word))
+}
+
+/// Only the states a ghost can be forced into (see StateOverrides) are allowed.
+fn parse_state(word: &str) -> Result<State, String> {
+    match word {
+        "Scatter" => Ok(Scatter),
+        "Chase" => Ok(Chase),
+        "Frightened" => Ok(Frightened),
+        _ => Err(format!("'{}' is no state ghosts can be forced into", word)),
+    }
+}
+
+/// Writes the command the same way it is parsed, so recorded scripts can be run again.
+impl Display for ConsoleCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsoleCommand::Level(level) => write!(f, "level {}", level),
+            ConsoleCommand::Lives(lives) => write!(f, "lives {}", lives),
+            ConsoleCommand::Score(score) => write!(f, "score {}", score),
+            ConsoleCommand::God => write!(f, "god"),
+            ConsoleCommand::FreezeGhosts => write!(f, "freeze ghosts"),
+            ConsoleCommand::SpawnFruit(fruit) => write!(f, "spawn fruit {:?}", fruit),
+            ConsoleCommand::State(Some(state)) => write!(f, "state {}", state),
+            ConsoleCommand::State(None) => write!(f, "state clear"),
+            ConsoleCommand::Seed(seed) => write!(f, "seed {}", seed),
+            ConsoleCommand::Map(name) => write!(f, "map {}", name),
+            ConsoleCommand::Speed(speed) => write!(f, "speed {}", speed),
+            ConsoleCommand::Record => write!(f, "record"),
+            ConsoleCommand::Save(file) => write!(f, "save {}", file),
+            ConsoleCommand::Run(file) => write!(f, "run {}", file),
+        }
+    }
+}
+
+/// Parse a script with one command per line. Empty lines and lines starting with '#' are skipped.
+pub(in crate::debug) fn parse_script(script: &str) -> Result<Vec<ConsoleCommand>, String> {
+    script
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| line.parse().map_err(|e| format!("line {}: {}", number, e)))
+        .collect()
+}
+
+/// Write the commands as a script which [parse_script] reads again.
+pub(in crate::debug) fn write_script(commands: &[ConsoleCommand]) -> String {
+    commands
+        .iter()
+        .map(|command| format!("{}\n", command))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::debug::console::command::ConsoleCommand::*;
+    use crate::debug::console::command::{parse_script, write_script, ConsoleCommand};
+    use crate::edibles::fruit::Fruit;
+    use crate::ghosts::state::State::*;
+
+    #[test]
+    fn every_command_can_be_parsed() {
+        let commands = [
+            ("level 7", Level(7)),
+            ("lives 5", Lives(5)),
+            ("score 12000", Score(12000)),
+            ("god", God),
+            ("freeze ghosts", FreezeGhosts),
+            ("spawn fruit Key", SpawnFruit(Fruit::Key)),
+            ("state Frightened", State(Some(Frightened))),
+            ("state clear", State(None)),
+            ("seed 42", Seed(42)),
+            ("map maze2", Map("maze2".to_string())),
+            ("speed 0.5", Speed(0.5)),
+            ("record", Record),
+            ("save test.script", Save("test.script".to_string())),
+            ("run test.script", Run("test.script".to_string())),
+        ];
+
+        for (text, command) in commands {
+            assert_eq!(text.parse::<ConsoleCommand>(), Ok(command.clone()));
+            assert_eq!(command.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn invalid_commands_are_rejected() {
+        for text in [
+            "",
+            "level",
+            "level 0",
+            "level seven",
+            "lives -1",
+            "state Eaten",
+            "spawn fruit Banana",
+            "speed 0",
+            "map",
+            "jump",
+        ] {
+            assert!(
+                text.parse::<ConsoleCommand>().is_err(),
+                "'{}' should be rejected",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn scripts_skip_comments_and_empty_lines() {
+        let script = "# start in level 7\nlevel 7\n\n  god  \n";
+
+        assert_eq!(parse_script(script), Ok(vec![Level(7), God]));
+    }
+
+    #[test]
+    fn script_errors_name_the_line() {
+        let error = parse_script("level 7\nlevel seven").unwrap_err();
+
+        assert!(error.starts_with("line 2:"), "{}", error);
+    }
+
+    #[test]
+    fn written_scripts_can_be_parsed_again() {
+        let commands = vec![
+            Level(3),
+            SpawnFruit(Fruit::Bell),
+            Speed(0.25),
+            State(Some(Chase)),
+        ];
+
+        assert_eq!(parse_script(&write_script(&commands)), Ok(commands));
+    }
+}
@@ -0,0 +1,523 @@
+use std::fs;
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::window::ReceivedCharacter;
+
+use crate::board_dimensions::BoardDimensions;
+use crate::clock::TimeScale;
+use crate::constants::{FONT, TEXT_Z};
+use crate::debug::console::command::{parse_script, write_script, ConsoleCommand};
+use crate::debug::DebugElement;
+use crate::edibles::energizer::EnergizerTimer;
+use crate::edibles::fruit::spawn_fruit;
+use crate::game_assets::loaded_assets::LoadedAssets;
+use crate::ghosts::movement::GhostsFrozen;
+use crate::ghosts::state::StateOverrides;
+use crate::interactions::GodMode;
+use crate::level::Level;
+use crate::life_cycle::LifeCycle;
+use crate::life_cycle::LifeCycle::{Loading, Running, Start};
+use crate::lives::{set_lives, Life};
+use crate::map::{build_board_and_map, Map, MapName};
+use crate::network::Lockstep;
+use crate::players::{GameMode, Player};
+use crate::random::Random;
+use crate::score::Score;
+
+mod command;
+
+/// How many lines of output the console shows above the input line.
+const CONSOLE_LINES: usize = 8;
+/// How deep scripts can run other scripts, so a script that runs itself stops.
+const MAX_SCRIPT_DEPTH: usize = 8;
+/// Printed for every command that would change the game in a network game.
+const NETWORK_GAME_REFUSAL: &str = "the game cannot change in a network game";
+
+/// An in-game console that executes commands on the running game.
+///
+/// '`' opens and closes the console, 'Escape' closes it too. While it is open, the keyboard only types into the
+/// console. 'Return' executes the typed command (see [ConsoleCommand] for every command).
+///
+/// Commands can be recorded into scripts with 'record' and 'save <file>'. 'run <file>' or '--script <file>' on the
+/// command line execute a script (the latter as soon as the game runs for the first time).
+pub(in crate::debug) struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_event::<EConsoleCommand>()
+            .add_system_set(SystemSet::on_exit(Loading).with_system(spawn_console))
+            .add_system_set(SystemSet::on_enter(Running).with_system(run_script_from_args))
+            .add_system_to_stage(CoreStage::PreUpdate, type_into_console.after(InputSystem))
+            .add_system(apply_progress_commands)
+            .add_system(apply_lives_commands)
+            .add_system(apply_cheat_commands)
+            .add_system(apply_spawn_commands)
+            .add_system(apply_map_commands)
+            .add_system(update_console_text);
+    }
+}
+
+/// Fired for every command the console executes on the game.
+#[derive(Deref)]
+struct EConsoleCommand(ConsoleCommand);
+
+#[derive(Default)]
+struct Console {
+    open: bool,
+    input: String,
+    output: Vec<String>,
+    recording: Option<Vec<ConsoleCommand>>,
+}
+
+impl Console {
+    fn print(&mut self, line: impl ToString) {
+        self.output.push(line.to_string())
+    }
+
+    /// Commands for the console itself are handled right here, every other command is sent to the game (and
+    /// recorded if a recording is running).
+    ///
+    /// In a network game, every command that changes the game is refused. It would only change this instance,
+    /// which then runs out of sync with the other one.
+    fn execute(
+        &mut self,
+        command: ConsoleCommand,
+        event_writer: &mut EventWriter<EConsoleCommand>,
+        network_game: bool,
+    ) {
+        self.execute_at_depth(command, event_writer, network_game, 0)
+    }
+
+    /// The depth counts the scripts the command is nested in.
+    fn execute_at_depth(
+        &mut self,
+        command: ConsoleCommand,
+        event_writer: &mut EventWriter<EConsoleCommand>,
+        network_game: bool,
+        depth: usize,
+    ) {
+        match command {
+            ConsoleCommand::Record => {
+                self.recording = Some(vec![]);
+                self.print("recording commands, save them with 'save <file>'")
+            }
+            ConsoleCommand::Save(file) => match self.recording.take() {
+                Some(recorded) => match fs::write(&file, write_script(&recorded)) {
+                    Ok(()) => self.print(format!("saved {} commands to {}", recorded.len(), file)),
+                    Err(e) => {
+                        self.print(format!("could not save {}: {}", file, e));
+                        self.recording = Some(recorded)
+                    }
+                },
+                None => self.print("nothing recorded, start with 'record'"),
+            },
+            ConsoleCommand::Run(file) if depth == MAX_SCRIPT_DEPTH => self.print(format!(
+                "could not run {}: scripts are nested deeper than {}",
+                file, MAX_SCRIPT_DEPTH
+            )),
+            ConsoleCommand::Run(file) => {
+                let script = fs::read_to_string(&file)
+                    .map_err(|e| e.to_string())
+                    .and_then(|script| parse_script(&script));
+
+                match script {
+                    Ok(commands) => commands.into_iter().for_each(|c| {
+                        self.execute_at_depth(c, event_writer, network_game, depth + 1)
+                    }),
+                    Err(e) => self.print(format!("could not run {}: {}", file, e)),
+                }
+            }
+            _ if network_game => self.print(NETWORK_GAME_REFUSAL),
+            command => {
+                if let Some(recorded) = &mut self.recording {
+                    recorded.push(command.clone())
+                }
+                event_writer.send(EConsoleCommand(command))
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn spawn_console(mut commands: Commands, game_asset_handles: Res<LoadedAssets>) {
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                String::new(),
+                TextStyle {
+                    font: game_asset_handles.get_handle(FONT),
+                    font_size: 7.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment {
+                vertical: VerticalAlign::Bottom,
+                horizontal: HorizontalAlign::Left,
+            }),
+            transform: Transform::from_xyz(5.0, 5.0, TEXT_Z),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(ConsoleText)
+        .insert(DebugElement);
+}
+
+/// Read the script to run from the command line, passed like '--script start.script'.
+fn script_from_args() -> Option<String> {
+    let args = std::env::args().collect::<Vec<_>>();
+    args.iter()
+        .position(|arg| arg == "--script")
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+fn run_script_from_args(
+    mut console: ResMut<Console>,
+    mut event_writer: EventWriter<EConsoleCommand>,
+    mut script_started: Local<bool>,
+    lockstep: Option<Res<Lockstep>>,
+) {
+    if *script_started {
+        return;
+    }
+    *script_started = true;
+
+    if let Some(file) = script_from_args() {
+        console.execute(
+            ConsoleCommand::Run(file),
+            &mut event_writer,
+            lockstep.is_some(),
+        )
+    }
+}
+
+/// Runs right after bevy collected the keyboard input. While the console is open, the input is reset afterwards,
+/// so neither the players nor the other debug hotkeys react to typing.
+fn type_into_console(
+    mut console: ResMut<Console>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut char_reader: EventReader<ReceivedCharacter>,
+    mut event_writer: EventWriter<EConsoleCommand>,
+    lockstep: Option<Res<Lockstep>>,
+) {
+    let typed = char_reader
+        .iter()
+        .map(|event| event.char)
+        .filter(|c| !c.is_control() && *c != '`')
+        .collect::<String>();
+
+    if keyboard_input.just_pressed(KeyCode::Grave) {
+        console.open = !console.open;
+        keyboard_input.reset_all();
+        return;
+    }
+
+    if !console.open {
+        return;
+    }
+
+    console.input.push_str(&typed);
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        console.open = false;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        console.print(format!("> {}", line));
+
+        match line.parse() {
+            Ok(command) => console.execute(command, &mut event_writer, lockstep.is_some()),
+            Err(e) => console.print(e),
+        }
+    }
+
+    keyboard_input.reset_all();
+}
+
+fn update_console_text(
+    console: Res<Console>,
+    mut query: Query<(&mut Text, &mut Visibility), With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    let first_line = console.output.len().saturating_sub(CONSOLE_LINES);
+    let output = console.output[first_line..]
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect::<String>();
+
+    for (mut text, mut visibility) in &mut query {
+        text.sections[0].value = format!("{}> {}_", output, console.input);
+        visibility.is_visible = console.open;
+    }
+}
+
+/// Level and score.
+fn apply_progress_commands(
+    mut event_reader: EventReader<EConsoleCommand>,
+    game_mode: Res<GameMode>,
+    mut level: ResMut<Level>,
+    mut score: ResMut<Score>,
+) {
+    for command in event_reader.iter() {
+        match **command {
+            ConsoleCommand::Level(new_level) => **level = new_level,
+            ConsoleCommand::Score(points) => {
+                for player in game_mode.capman_players() {
+                    score.set(player, points)
+                }
+            }
+            _ => continue,
+        }
+    }
+}
+
+/// The lives get their own system, as they are shown by entities on the board.
+fn apply_lives_commands(
+    mut commands: Commands,
+    mut event_reader: EventReader<EConsoleCommand>,
+    game_asset_handles: Option<Res<LoadedAssets>>,
+    dimensions: Option<Res<BoardDimensions>>,
+    game_mode: Res<GameMode>,
+    life_query: Query<(Entity, &Life, &Player)>,
+) {
+    for command in event_reader.iter() {
+        let lives = match **command {
+            ConsoleCommand::Lives(lives) => lives,
+            _ => continue,
+        };
+        let (game_asset_handles, dimensions) = match (&game_asset_handles, &dimensions) {
+            (Some(assets), Some(dimensions)) => (assets, dimensions),
+            _ => continue,
+        };
+
+        for player in game_mode.capman_players() {
+            set_lives(
+                &mut commands,
+                game_asset_handles,
+                dimensions,
+                player,
+                lives,
+                &life_query,
+            )
+        }
+    }
+}
+
+/// Commands that change the rules of the game.
+fn apply_cheat_commands(
+    mut event_reader: EventReader<EConsoleCommand>,
+    mut console: ResMut<Console>,
+    mut god_mode: ResMut<GodMode>,
+    mut ghosts_frozen: ResMut<GhostsFrozen>,
+    mut state_overrides: ResMut<StateOverrides>,
+    mut random: ResMut<Random>,
+    mut time_scale: ResMut<TimeScale>,
+) {
+    for command in event_reader.iter() {
+        match **command {
+            ConsoleCommand::God => {
+                **god_mode = !**god_mode;
+                console.print(format!("god mode {}", on_off(**god_mode)))
+            }
+            ConsoleCommand::FreezeGhosts => {
+                **ghosts_frozen = !**ghosts_frozen;
+                console.print(format!("ghosts frozen {}", on_off(**ghosts_frozen)))
+            }
+            ConsoleCommand::State(Some(state)) => state_overrides.force_all(state),
+            ConsoleCommand::State(None) => state_overrides.clear(),
+            ConsoleCommand::Seed(seed) => *random = Random::from_seed(seed),
+            ConsoleCommand::Speed(speed) => time_scale.set_scale(speed),
+            _ => continue,
+        }
+    }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Fruits, spawned like the ones the game spawns itself.
+fn apply_spawn_commands(
+    mut commands: Commands,
+    mut event_reader: EventReader<EConsoleCommand>,
+    game_asset_handles: Option<Res<LoadedAssets>>,
+    map: Option<Res<Map>>,
+    dimensions: Option<Res<BoardDimensions>>,
+) {
+    let (game_asset_handles, map, dimensions) = match (game_asset_handles, map, dimensions) {
+        (Some(assets), Some(map), Some(dimensions)) => (assets, map, dimensions),
+        _ => return,
+    };
+
+    for command in event_reader.iter() {
+        if let ConsoleCommand::SpawnFruit(fruit) = **command {
+            spawn_fruit(&mut commands, &game_asset_handles, &map, &dimensions, fruit)
+        }
+    }
+}
+
+/// Switching the map starts the current level again on the new board. Every entity except the ones of the debug
+/// tools is despawned, the board is built again and the game enters Start, which spawns everything anew. The level,
+/// the score and every setting are kept, the lives are reset and a running energizer ends.
+///
+/// Only the last map of the frame is played, as the game starts only once.
+fn apply_map_commands(
+    mut commands: Commands,
+    mut event_reader: EventReader<EConsoleCommand>,
+    mut console: ResMut<Console>,
+    game_asset_handles: Option<Res<LoadedAssets>>,
+    mut map_name: ResMut<MapName>,
+    mut life_cycle: ResMut<State<LifeCycle>>,
+    query: Query<Entity, Without<DebugElement>>,
+) {
+    let new_map_name = event_reader
+        .iter()
+        .filter_map(|command| match &**command {
+            ConsoleCommand::Map(name) => Some(MapName(name.clone())),
+            _ => None,
+        })
+        .next_back();
+    let (new_map_name, game_asset_handles) = match (new_map_name, game_asset_handles) {
+        (Some(name), Some(assets)) => (name, assets),
+        _ => return,
+    };
+
+    if life_cycle.current() != &Running {
+        console.print("maps can only be switched while the game runs");
+        return;
+    }
+    if !new_map_name.exists(&game_asset_handles) {
+        console.print(format!(
+            "there is no map '{}' in assets/maps",
+            *new_map_name
+        ));
+        return;
+    }
+
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<EnergizerTimer>();
+    *map_name = new_map_name;
+    commands.add(build_board_and_map);
+    life_cycle.set(Start).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use bevy::ecs::system::SystemState;
+    use bevy::prelude::*;
+
+    use bevy::window::ReceivedCharacter;
+
+    use crate::clock::TimeScale;
+    use crate::debug::console::command::ConsoleCommand;
+    use crate::debug::console::{
+        Console, ConsolePlugin, EConsoleCommand, MAX_SCRIPT_DEPTH, NETWORK_GAME_REFUSAL,
+    };
+    use crate::edibles::fruit::Fruit;
+    use crate::game::headless_app;
+    use crate::level::Level;
+    use crate::life_cycle::LifeCycle;
+    use crate::life_cycle::LifeCycle::{Running, Start};
+
+    /// Execute the command and return the console output and the number of commands sent to the game.
+    fn execute(command: ConsoleCommand, network_game: bool) -> (Vec<String>, usize) {
+        let mut world = World::new();
+        world.init_resource::<Console>();
+        world.init_resource::<Events<EConsoleCommand>>();
+        let mut system_state =
+            SystemState::<(ResMut<Console>, EventWriter<EConsoleCommand>)>::new(&mut world);
+        let (mut console, mut event_writer) = system_state.get_mut(&mut world);
+        console.execute(command, &mut event_writer, network_game);
+        let output = console.output.clone();
+
+        (output, world.resource::<Events<EConsoleCommand>>().len())
+    }
+
+    #[test]
+    fn a_script_that_runs_itself_stops_at_the_maximum_depth() {
+        let file_name = format!("capman_runs_itself_{}.script", std::process::id());
+        let file = std::env::temp_dir().join(file_name);
+        let file = file.to_str().unwrap().to_string();
+        fs::write(&file, format!("god\nrun {}\n", file)).unwrap();
+
+        let (output, sent_commands) = execute(ConsoleCommand::Run(file.clone()), false);
+        fs::remove_file(&file).unwrap();
+
+        assert_eq!(sent_commands, MAX_SCRIPT_DEPTH);
+        assert_eq!(
+            output,
+            vec![format!(
+                "could not run {}: scripts are nested deeper than {}",
+                file, MAX_SCRIPT_DEPTH
+            )]
+        );
+    }
+
+    #[test]
+    fn commands_that_change_the_game_are_refused_in_a_network_game() {
+        for command in [
+            ConsoleCommand::Level(7),
+            ConsoleCommand::Lives(5),
+            ConsoleCommand::God,
+            ConsoleCommand::Seed(42),
+            ConsoleCommand::Map("maze2".to_string()),
+            ConsoleCommand::SpawnFruit(Fruit::Key),
+            ConsoleCommand::Speed(0.5),
+        ] {
+            let (output, sent_commands) = execute(command.clone(), true);
+
+            assert_eq!(sent_commands, 0, "'{}' should be refused", command);
+            assert_eq!(output, vec![NETWORK_GAME_REFUSAL.to_string()]);
+        }
+    }
+
+    #[test]
+    fn switching_the_map_starts_the_level_again_on_the_new_board() {
+        let mut app = headless_app(60);
+        app.add_event::<ReceivedCharacter>()
+            .init_resource::<TimeScale>()
+            .add_plugin(ConsolePlugin)
+            .insert_resource(Level(3));
+        update_until(&mut app, Running);
+        let entities = app.world.entities().len();
+
+        app.world
+            .send_event(EConsoleCommand(ConsoleCommand::Map("default".to_string())));
+        update_until(&mut app, Start);
+        update_until(&mut app, Running);
+
+        assert_eq!(app.world.entities().len(), entities);
+        assert_eq!(**app.world.resource::<Level>(), 3);
+    }
+
+    fn update_until(app: &mut App, life_cycle: LifeCycle) {
+        for _ in 0..1000 {
+            app.update();
+
+            if app.world.resource::<State<LifeCycle>>().current() == &life_cycle {
+                return;
+            }
+        }
+        panic!("the game never reached {:?}", life_cycle)
+    }
+}
@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+
+use crate::board_dimensions::BoardDimensions;
+use crate::common::position::Position;
+use crate::constants::{DEBUG_OVERLAY_Z, FONT};
+use crate::debug::{
+    ghost_color, DebugElement, BLINKY_COLOR, CAPMAN_COLOR, CLYDE_COLOR, INKY_COLOR, PINKY_COLOR,
+};
+use crate::game_assets::loaded_assets::LoadedAssets;
+use crate::map::board::Board;
+use crate::map::Element;
+use crate::map::Element::*;
+use crate::map::Map;
+
+const CELL_ALPHA: f32 = 0.5;
+const EMPTY_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.05);
+const WALL_COLOR: Color = Color::rgb(0.5, 0.5, 0.5);
+const INVISIBLE_WALL_COLOR: Color = Color::rgb(1.0, 0.0, 1.0);
+const GHOST_HOUSE_ENTRANCE_COLOR: Color = Color::rgb(1.0, 0.5, 0.0);
+const TUNNEL_COLOR: Color = Color::rgb(0.0, 0.8, 0.0);
+const TUNNEL_HALLWAY_COLOR: Color = Color::rgb(0.0, 0.4, 0.8);
+const FRUIT_SPAWN_COLOR: Color = Color::rgb(0.6, 1.0, 0.6);
+
+/// Shows how the board and the map see every field, which helps map authors with elements that are invisible in the
+/// game (like invisible walls or tunnel hallways).
+///
+/// 'M' toggles the grid. Every field is colored by its map element (corners in the ghosts colors, capmans spawn,
+/// the fruit spawn, invisible walls and tunnel hallways) or else by its board classification (wall, ghost house
+/// entrance, tunnel), and shows its position. A left click on a field logs its position and elements.
+pub(in crate::debug) struct GridDebugPlugin;
+
+impl Plugin for GridDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GridVisible>()
+            .add_system(toggle_grid)
+            .add_system(spawn_grid.after(toggle_grid))
+            .add_system(log_clicked_field);
+    }
+}
+
+#[derive(Default, Deref, DerefMut)]
+struct GridVisible(bool);
+
+#[derive(Component)]
+struct GridElement;
+
+fn toggle_grid(
+    keyboard_input: Res<Input<KeyCode>>,
+    board: Option<Res<Board>>,
+    mut grid_visible: ResMut<GridVisible>,
+) {
+    if keyboard_input.just_pressed(KeyCode::M) && board.is_some() {
+        **grid_visible = !**grid_visible;
+    }
+}
+
+/// The grid is spawned when it gets visible or the board changed, so it always shows the current board.
+fn spawn_grid(
+    mut commands: Commands,
+    game_asset_handles: Res<LoadedAssets>,
+    map: Option<Res<Map>>,
+    board: Option<Res<Board>>,
+    dimensions: Option<Res<BoardDimensions>>,
+    grid_visible: Res<GridVisible>,
+    grid_query: Query<Entity, With<GridElement>>,
+) {
+    let board_changed = board.as_ref().is_some_and(|board| board.is_changed());
+    if !grid_visible.is_changed() && !board_changed {
+        return;
+    }
+
+    for entity in &grid_query {
+        commands.entity(entity).despawn();
+    }
+
+    let (map, board, dimensions) = match (map, board, dimensions) {
+        (Some(m), Some(b), Some(d)) if **grid_visible => (m, b, d),
+        _ => return,
+    };
+
+    let font = game_asset_handles.get_handle(FONT);
+    let field = dimensions.field();
+
+    for x in 0..board.width as isize {
+        for y in 0..board.height as isize {
+            let position = Position::new(x, y);
+            let coordinates = dimensions.pos_to_vec(&position, DEBUG_OVERLAY_Z - 1.0);
+
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: cell_color(&board, &position, map.elements_at(&position)),
+                        custom_size: Some(Vec2::splat(field - 1.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(coordinates),
+                    ..default()
+                })
+                .insert(GridElement)
+                .insert(DebugElement);
+
+            commands
+                .spawn_bundle(Text2dBundle {
+                    text: Text::from_section(
+                        format!("{},{}", x, y),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 4.0,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_alignment(TextAlignment::CENTER),
+                    transform: Transform::from_translation(
+                        coordinates.truncate().extend(DEBUG_OVERLAY_Z),
+                    ),
+                    ..default()
+                })
+                .insert(GridElement)
+                .insert(DebugElement);
+        }
+    }
+}
+
+/// The camera shows the window 1:1, so the cursor position is also the position in the world.
+fn log_clicked_field(
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    grid_visible: Res<GridVisible>,
+    map: Option<Res<Map>>,
+    dimensions: Option<Res<BoardDimensions>>,
+) {
+    if !**grid_visible || !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let cursor = match windows.get_primary().and_then(Window::cursor_position) {
+        Some(c) => c,
+        None => return,
+    };
+    let (map, dimensions) = match (map, dimensions) {
+        (Some(m), Some(d)) => (m, d),
+        _ => return,
+    };
+
+    let position = dimensions.vec_to_pos(&cursor.extend(0.0));
+    info!("{}: {:?}", position, map.elements_at(&position))
+}
+
+/// Map elements win over the board classification, as they are more specific.
+fn cell_color(board: &Board, position: &Position, elements: &[Element]) -> Color {
+    match elements
+        .iter()
+        .find_map(element_color)
+        .or_else(|| board_color(board, position))
+    {
+        Some(mut color) => *color.set_a(CELL_ALPHA),
+        None => EMPTY_COLOR,
+    }
+}
+
+fn element_color(element: &Element) -> Option<Color> {
+    match element {
+        InvisibleWall => Some(INVISIBLE_WALL_COLOR),
+        TunnelHallway => Some(TUNNEL_HALLWAY_COLOR),
+        CapManSpawn => Some(CAPMAN_COLOR),
+        FruitSpawn => Some(FRUIT_SPAWN_COLOR),
+        BlinkyCorner => Some(BLINKY_COLOR),
+        PinkyCorner => Some(PINKY_COLOR),
+        InkyCorner => Some(INKY_COLOR),
+        ClydeCorner => Some(CLYDE_COLOR),
+        GhostCorner { ghost, .. } => Some(ghost_color(ghost)),
+        _ => None,
+    }
+}
+
+fn board_color(board: &Board, position: &Position) -> Option<Color> {
+    if board.position_is_wall(position) {
+        Some(WALL_COLOR)
+    } else if board.position_is_ghost_house_entrance(position) {
+        Some(GHOST_HOUSE_ENTRANCE_COLOR)
+    } else if board.position_is_tunnel(position) {
+        Some(TUNNEL_COLOR)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Color;
+
+    use crate::common::position::Position;
+    use crate::debug::grid::{cell_color, EMPTY_COLOR, INVISIBLE_WALL_COLOR, TUNNEL_COLOR};
+    use crate::map::board::Board;
+    use crate::map::Element::*;
+    use crate::map::{Element, Field, Map};
+
+    fn color_of(elements: Vec<Element>) -> Color {
+        let position = Position::new(0, 0);
+        let map = Map::from_fields(
+            elements
+                .into_iter()
+                .map(|element| Field { position, element })
+                .collect(),
+        );
+        cell_color(&Board::new(&map), &position, map.elements_at(&position))
+    }
+
+    #[test]
+    fn map_elements_win_over_the_board_classification() {
+        // an invisible wall is also a wall on the board
+        assert_eq!(
+            color_of(vec![InvisibleWall]),
+            *INVISIBLE_WALL_COLOR.clone().set_a(0.5)
+        );
+    }
+
+    #[test]
+    fn fields_without_special_elements_use_the_board_classification() {
+        assert_eq!(
+            color_of(vec![TunnelEntrance]),
+            *TUNNEL_COLOR.clone().set_a(0.5)
+        );
+        assert_eq!(color_of(vec![DotSpawn]), EMPTY_COLOR);
+    }
+}
@@ -0,0 +1,393 @@
+use bevy::ecs::schedule::ShouldRun;
+use bevy::prelude::*;
+
+use crate::constants::{FONT, TEXT_Z, WINDOW_HEIGHT};
+use crate::debug::DebugElement;
+use crate::game_assets::loaded_assets::LoadedAssets;
+
+const PANEL_X: f32 = 5.0;
+const PANEL_TOP: f32 = WINDOW_HEIGHT - 5.0;
+const PANEL_WIDTH: f32 = 330.0;
+const LINE_HEIGHT: f32 = 11.0;
+const FONT_SIZE: f32 = 7.0;
+const HEADER_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+
+/// A panel that lists named watches, sorted into collapsible groups.
+///
+/// Every plugin can add watches with [InspectorApp::add_watch]. 'B' shows and hides the panel, clicking a group
+/// header collapses or expands the group.
+pub(in crate::debug) struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inspector>()
+            .add_system(toggle_inspector_visibility)
+            .add_system(collapse_group_on_click)
+            .add_system(render_panel.after(LInspectorWatch));
+    }
+}
+
+/// Marks every system that updates a watch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct LInspectorWatch;
+
+pub trait InspectorApp {
+    /// Add a watch to the given group of the inspector panel. Groups are listed in the order they got their first watch.
+    ///
+    /// The watch is a system (usually a closure over resources or queries) which returns what to display: a String,
+    /// a String with a Color, or several rows with their own colors. It only runs while the panel is visible.
+    fn add_watch<Out, Params>(
+        &mut self,
+        group: &'static str,
+        name: &'static str,
+        watch: impl IntoSystem<(), Out, Params>,
+    ) -> &mut Self
+    where
+        Out: Into<WatchValue> + 'static;
+}
+
+impl InspectorApp for App {
+    fn add_watch<Out, Params>(
+        &mut self,
+        group: &'static str,
+        name: &'static str,
+        watch: impl IntoSystem<(), Out, Params>,
+    ) -> &mut Self
+    where
+        Out: Into<WatchValue> + 'static,
+    {
+        let id = self
+            .init_resource::<Inspector>()
+            .world
+            .resource_mut::<Inspector>()
+            .register(group, name);
+
+        let store_value = move |In(value): In<Out>, mut inspector: ResMut<Inspector>| {
+            inspector.set(id, value.into())
+        };
+
+        self.add_system(
+            watch
+                .chain(store_value)
+                .with_run_criteria(inspector_visible)
+                .label(LInspectorWatch),
+        )
+    }
+}
+
+/// What a watch displays.
+pub enum WatchValue {
+    /// Displayed as "name: value" in the given color.
+    Line(String, Color),
+    /// Every row is displayed on its own line in its color, without the name of the watch.
+    Rows(Vec<(String, Color)>),
+}
+
+impl From<String> for WatchValue {
+    fn from(value: String) -> Self {
+        WatchValue::Line(value, Color::WHITE)
+    }
+}
+
+impl From<(String, Color)> for WatchValue {
+    fn from((value, color): (String, Color)) -> Self {
+        WatchValue::Line(value, color)
+    }
+}
+
+impl From<Vec<(String, Color)>> for WatchValue {
+    fn from(rows: Vec<(String, Color)>) -> Self {
+        WatchValue::Rows(rows)
+    }
+}
+
+/// Identifies a watch by the index of its group and its index in the group.
+#[derive(Copy, Clone, Debug)]
+struct WatchId(usize, usize);
+
+#[derive(Default)]
+pub struct Inspector {
+    visible: bool,
+    groups: Vec<Group>,
+}
+
+struct Group {
+    name: &'static str,
+    collapsed: bool,
+    watches: Vec<Watch>,
+}
+
+struct Watch {
+    name: &'static str,
+    value: Option<WatchValue>,
+}
+
+/// A line of the panel. Clicking a line with a group collapses the group.
+#[derive(Debug, PartialEq)]
+struct PanelLine {
+    text: String,
+    color: Color,
+    group: Option<usize>,
+}
+
+impl Inspector {
+    fn register(&mut self, group_name: &'static str, name: &'static str) -> WatchId {
+        let group_index = match self.groups.iter().position(|g| g.name == group_name) {
+            Some(index) => index,
+            None => {
+                self.groups.push(Group {
+                    name: group_name,
+                    collapsed: false,
+                    watches: vec![],
+                });
+                self.groups.len() - 1
+            }
+        };
+
+        let watches = &mut self.groups[group_index].watches;
+        watches.push(Watch { name, value: None });
+        WatchId(group_index, watches.len() - 1)
+    }
+
+    fn set(&mut self, id: WatchId, value: WatchValue) {
+        self.groups[id.0].watches[id.1].value = Some(value)
+    }
+
+    fn toggle_collapsed(&mut self, group: usize) {
+        if let Some(group) = self.groups.get_mut(group) {
+            group.collapsed = !group.collapsed
+        }
+    }
+
+    /// Every group has a header line, followed by the lines of its watches if it is not collapsed.
+    fn lines(&self) -> Vec<PanelLine> {
+        let mut lines = vec![];
+
+        for (i, group) in self.groups.iter().enumerate() {
+            lines.push(PanelLine {
+                text: format!(
+                    "{} {}",
+                    if group.collapsed { "[+]" } else { "[-]" },
+                    group.name
+                ),
+                color: HEADER_COLOR,
+                group: Some(i),
+            });
+
+            if group.collapsed {
+                continue;
+            }
+
+            for watch in &group.watches {
+                match &watch.value {
+                    Some(WatchValue::Line(value, color)) => lines.push(PanelLine {
+                        text: format!("  {}: {}", watch.name, value),
+                        color: *color,
+                        group: None,
+                    }),
+                    Some(WatchValue::Rows(rows)) => {
+                        lines.extend(rows.iter().map(|(row, color)| PanelLine {
+                            text: format!("  {}", row),
+                            color: *color,
+                            group: None,
+                        }))
+                    }
+                    None => lines.push(PanelLine {
+                        text: format!("  {}: -", watch.name),
+                        color: Color::WHITE,
+                        group: None,
+                    }),
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+fn inspector_visible(inspector: Res<Inspector>) -> ShouldRun {
+    match inspector.visible {
+        true => ShouldRun::Yes,
+        false => ShouldRun::No,
+    }
+}
+
+fn toggle_inspector_visibility(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut inspector: ResMut<Inspector>,
+) {
+    if keyboard_input.just_pressed(KeyCode::B) {
+        inspector.visible = !inspector.visible
+    }
+}
+
+/// The camera shows the window 1:1, so the cursor position is also the position in the world.
+fn collapse_group_on_click(
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut inspector: ResMut<Inspector>,
+) {
+    if !inspector.visible || !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let cursor = match windows.get_primary().and_then(Window::cursor_position) {
+        Some(c) if c.x <= PANEL_X + PANEL_WIDTH && c.y <= PANEL_TOP => c,
+        _ => return,
+    };
+    let line_index = ((PANEL_TOP - cursor.y) / LINE_HEIGHT) as usize;
+
+    if let Some(group) = inspector
+        .lines()
+        .get(line_index)
+        .and_then(|line| line.group)
+    {
+        inspector.toggle_collapsed(group)
+    }
+}
+
+#[derive(Component)]
+struct PanelLineText(usize);
+
+/// Every line is its own text, so a click can be mapped to its line. Lines are spawned and despawned as the number
+/// of lines changes.
+fn render_panel(
+    mut commands: Commands,
+    game_asset_handles: Option<Res<LoadedAssets>>,
+    inspector: Res<Inspector>,
+    mut query: Query<(Entity, &PanelLineText, &mut Text)>,
+) {
+    let game_asset_handles = match game_asset_handles {
+        Some(handles) => handles,
+        None => return,
+    };
+    let lines = match inspector.visible {
+        true => inspector.lines(),
+        false => vec![],
+    };
+    let mut existing_lines = 0;
+
+    for (entity, line_text, mut text) in &mut query {
+        match lines.get(line_text.0) {
+            Some(line) => {
+                text.sections[0].value = line.text.clone();
+                text.sections[0].style.color = line.color;
+                existing_lines += 1
+            }
+            None => commands.entity(entity).despawn(),
+        }
+    }
+
+    let font = game_asset_handles.get_handle(FONT);
+
+    for (i, line) in lines.iter().enumerate().skip(existing_lines) {
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    line.text.clone(),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: FONT_SIZE,
+                        color: line.color,
+                    },
+                )
+                .with_alignment(TextAlignment {
+                    vertical: VerticalAlign::Top,
+                    horizontal: HorizontalAlign::Left,
+                }),
+                transform: Transform::from_xyz(PANEL_X, PANEL_TOP - LINE_HEIGHT * i as f32, TEXT_Z),
+                ..default()
+            })
+            .insert(PanelLineText(i))
+            .insert(DebugElement);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::debug::inspector::{Inspector, InspectorApp, PanelLine, WatchValue, HEADER_COLOR};
+
+    fn texts(inspector: &Inspector) -> Vec<String> {
+        inspector
+            .lines()
+            .into_iter()
+            .map(|line| line.text)
+            .collect()
+    }
+
+    #[test]
+    fn watches_are_listed_below_their_group() {
+        let mut inspector = Inspector::default();
+        let level = inspector.register("Game", "Level");
+        let ghosts = inspector.register("Ghosts", "Ghosts");
+        let dots = inspector.register("Game", "Dots");
+        inspector.set(level, "7".to_string().into());
+        inspector.set(
+            ghosts,
+            vec![
+                ("Blinky".to_string(), Color::RED),
+                ("Pinky".to_string(), Color::PINK),
+            ]
+            .into(),
+        );
+        inspector.set(
+            dots,
+            WatchValue::Line("10 / 244".to_string(), Color::YELLOW),
+        );
+
+        assert_eq!(
+            texts(&inspector),
+            vec![
+                "[-] Game",
+                "  Level: 7",
+                "  Dots: 10 / 244",
+                "[-] Ghosts",
+                "  Blinky",
+                "  Pinky"
+            ]
+        );
+        assert_eq!(
+            inspector.lines()[0],
+            PanelLine {
+                text: "[-] Game".to_string(),
+                color: HEADER_COLOR,
+                group: Some(0)
+            }
+        );
+        assert_eq!(inspector.lines()[5].color, Color::PINK);
+    }
+
+    #[test]
+    fn collapsed_groups_only_show_their_header() {
+        let mut inspector = Inspector::default();
+        inspector.register("Game", "Level");
+        inspector.register("Timers", "Energizer");
+        inspector.toggle_collapsed(0);
+
+        assert_eq!(
+            texts(&inspector),
+            vec!["[+] Game", "[-] Timers", "  Energizer: -"]
+        );
+    }
+
+    #[derive(Deref)]
+    struct Counter(usize);
+
+    #[test]
+    fn watches_run_only_while_the_panel_is_visible() {
+        let mut app = App::new();
+        app.insert_resource(Counter(3))
+            .add_watch("Game", "Counter", |counter: Res<Counter>| {
+                counter.to_string()
+            });
+
+        app.update();
+        assert_eq!(texts(app.world.resource::<Inspector>())[1], "  Counter: -");
+
+        app.world.resource_mut::<Inspector>().visible = true;
+        app.update();
+        assert_eq!(texts(app.world.resource::<Inspector>())[1], "  Counter: 3");
+    }
+}
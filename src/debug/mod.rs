@@ -0,0 +1,203 @@
+use crate::board_dimensions::BoardDimensions;
+use crate::capman::Capman;
+use crate::common::Direction;
+use crate::edibles::dots::EatenDots;
+use crate::edibles::energizer::EnergizerTimer;
+use crate::edibles::fruit::FruitDespawnTimer;
+use crate::edibles::Edible;
+use crate::ghost_house_gate::GhostHouseGates;
+use crate::ghosts::state::State;
+use crate::ghosts::Ghost::*;
+use crate::ghosts::{Ghost, GhostId};
+use crate::level::Level;
+use crate::life_cycle::LifeCycle;
+use bevy::diagnostic::{Diagnostics, DiagnosticsPlugin, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use console::ConsolePlugin;
+use grid::GridDebugPlugin;
+use inspector::{InspectorApp, InspectorPlugin};
+use schedule::ScheduleDebugPlugin;
+use targets::TargetDebugPlugin;
+use time::TimeDebugPlugin;
+
+mod console;
+mod grid;
+pub mod inspector;
+mod schedule;
+mod targets;
+mod time;
+
+const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+const CAPMAN_COLOR: Color = Color::rgb(1.0, 1.0, 0.0);
+const BLINKY_COLOR: Color = Color::rgb(1.0, 0.0, 0.0);
+const PINKY_COLOR: Color = Color::rgb(1.0, 156.0 / 255.0, 206.0 / 255.0);
+const INKY_COLOR: Color = Color::rgb(49.0 / 255.0, 1.0, 1.0);
+const CLYDE_COLOR: Color = Color::rgb(1.0, 206.0 / 255.0, 49.0 / 255.0);
+
+/// Marks every entity of the debug tools. They stay when the console switches the map, while everything else
+/// gets despawned.
+#[derive(Component)]
+struct DebugElement;
+
+fn ghost_color(ghost: &Ghost) -> Color {
+    match ghost {
+        Blinky => BLINKY_COLOR,
+        Pinky => PINKY_COLOR,
+        Inky => INKY_COLOR,
+        Clyde => CLYDE_COLOR,
+    }
+}
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(DiagnosticsPlugin)
+            .add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_plugin(InspectorPlugin)
+            .add_watch("Game", "FPS", fps)
+            .add_watch(
+                "Game",
+                "LifeCycle",
+                |life_cycle: Res<bevy::prelude::State<LifeCycle>>| {
+                    format!("{:?}", life_cycle.current())
+                },
+            )
+            .add_watch("Game", "Level", |level: Res<Level>| level.0.to_string())
+            .add_watch(
+                "Game",
+                "Dots",
+                |eaten_dots: Option<Res<EatenDots>>| match eaten_dots {
+                    Some(eaten_dots) => {
+                        format!("{} / {}", eaten_dots.get_eaten(), eaten_dots.get_max())
+                    }
+                    None => "-".to_string(),
+                },
+            )
+            .add_watch("Capman", "Capman", capman_rows)
+            .add_watch("Ghosts", "Ghosts", ghost_rows)
+            .add_watch(
+                "Timers",
+                "Energizer",
+                |timer: Option<Res<EnergizerTimer>>| match timer {
+                    Some(timer) => format!("{:.2}", timer.remaining()),
+                    None => "-".to_string(),
+                },
+            )
+            .add_watch(
+                "Timers",
+                "Fruit",
+                |timer: Option<Res<FruitDespawnTimer>>| match timer {
+                    Some(timer) => format!(
+                        "{:.2}",
+                        timer.duration().as_secs_f32() - timer.elapsed_secs()
+                    ),
+                    None => "-".to_string(),
+                },
+            )
+            .add_watch("Ghost house gates", "Gates", gate_rows)
+            .add_plugin(ConsolePlugin)
+            .add_plugin(GridDebugPlugin)
+            .add_plugin(ScheduleDebugPlugin)
+            .add_plugin(TargetDebugPlugin)
+            .add_plugin(TimeDebugPlugin)
+            .add_system(despawn_all_edibles_on_key_press);
+    }
+}
+
+fn fps(diagnostics: Res<Diagnostics>) -> String {
+    match diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|diagnostic| diagnostic.value())
+    {
+        Some(fps) => format!("{:.0}", fps),
+        None => "-".to_string(),
+    }
+}
+
+fn capman_rows(
+    dimensions: Option<Res<BoardDimensions>>,
+    query: Query<(&Transform, &Direction), With<Capman>>,
+) -> Vec<(String, Color)> {
+    let dimensions = match dimensions {
+        Some(d) => d,
+        None => return vec![],
+    };
+
+    query
+        .iter()
+        .map(|(transform, direction)| {
+            let coordinates = transform.translation;
+            let position = dimensions.vec_to_pos(&coordinates);
+            let text = format!(
+                "Capman: {}, {}, {}",
+                format_coordinates(coordinates),
+                position,
+                direction
+            );
+            (text, CAPMAN_COLOR)
+        })
+        .collect()
+}
+
+/// One row per ghost, ordered by their id, in the color of their kind.
+fn ghost_rows(
+    dimensions: Option<Res<BoardDimensions>>,
+    query: Query<(&Ghost, &GhostId, &Transform, &Direction, &State)>,
+) -> Vec<(String, Color)> {
+    let dimensions = match dimensions {
+        Some(d) => d,
+        None => return vec![],
+    };
+    let mut ghosts = query.iter().collect::<Vec<_>>();
+    ghosts.sort_by_key(|(_, id, ..)| **id);
+
+    ghosts
+        .into_iter()
+        .map(|(ghost, id, transform, direction, state)| {
+            let coordinates = transform.translation;
+            let position = dimensions.vec_to_pos(&coordinates);
+            let text = format!(
+                "{:?} {}: {}, {}, {}, {}",
+                ghost,
+                id.0,
+                format_coordinates(coordinates),
+                position,
+                direction,
+                state
+            );
+            (text, ghost_color(ghost))
+        })
+        .collect()
+}
+
+fn format_coordinates(coordinates: Vec3) -> String {
+    format!(
+        "({:.2}, {:.2}, {:.2})",
+        coordinates.x, coordinates.y, coordinates.z
+    )
+}
+
+fn gate_rows(ghost_house_gates: Option<Res<GhostHouseGates>>) -> Vec<(String, Color)> {
+    ghost_house_gates
+        .iter()
+        .flat_map(|gates| gates.iter())
+        .enumerate()
+        .map(|(i, gate)| (format!("Gate {}: {}", i, gate), WHITE))
+        .collect()
+}
+
+/// Despawn all dots when '1' was pressed.
+fn despawn_all_edibles_on_key_press(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    query: Query<Entity, With<Edible>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Key1) {
+        return;
+    }
+
+    for e in &query {
+        commands.entity(e).despawn();
+    }
+}
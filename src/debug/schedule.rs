@@ -0,0 +1,332 @@
+use bevy::prelude::*;
+
+use crate::constants::{FONT, TEXT_Z, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::debug::DebugElement;
+use crate::game_assets::loaded_assets::LoadedAssets;
+use crate::ghosts::schedule::Schedule;
+use crate::ghosts::state::State::*;
+use crate::ghosts::state::{State, StateOverrides};
+use crate::ghosts::{Ghost, GhostId};
+use crate::life_cycle::LifeCycle::Loading;
+use crate::network::Lockstep;
+
+const TIMELINE_WIDTH: f32 = 300.0;
+const TIMELINE_HEIGHT: f32 = 10.0;
+const TIMELINE_X: f32 = WINDOW_WIDTH - TIMELINE_WIDTH - 10.0;
+const TIMELINE_Y: f32 = WINDOW_HEIGHT - 20.0;
+const SCATTER_COLOR: Color = Color::rgb(0.2, 0.4, 1.0);
+const CHASE_COLOR: Color = Color::rgb(1.0, 0.3, 0.2);
+const PLAYHEAD_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
+
+/// Shows the scatter/chase schedule of the current level and lets you force ghosts into a state.
+///
+/// 'T' toggles the timeline. Every phase is one segment with its state and length, the playhead marks
+/// the current time. The time the schedule stood still because of an energizer is shown below.
+///
+/// 'F5', 'F6' and 'F7' force Scatter, Chase or Frightened, 'F8' removes every override. 'G' cycles
+/// through the ghosts the override applies to (all ghosts or a single one). Neither works in a network
+/// game.
+pub(in crate::debug) struct ScheduleDebugPlugin;
+
+impl Plugin for ScheduleDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimelineVisible>()
+            .init_resource::<OverrideSelection>()
+            .add_system_set(SystemSet::on_exit(Loading).with_system(spawn_timeline))
+            .add_system(toggle_timeline_visibility)
+            .add_system(rebuild_segments_when_phases_changed)
+            .add_system(update_playhead)
+            .add_system(update_timeline_text)
+            .add_system(select_ghost_to_override)
+            .add_system(override_states_on_key_press);
+    }
+}
+
+#[derive(Default, Deref, DerefMut)]
+struct TimelineVisible(bool);
+
+/// The ghost the next override applies to. None means all ghosts.
+#[derive(Default, Deref, DerefMut)]
+struct OverrideSelection(Option<GhostId>);
+
+#[derive(Component)]
+struct TimelineElement;
+
+#[derive(Component)]
+struct TimelineSegment;
+
+#[derive(Component)]
+struct Playhead;
+
+#[derive(Component)]
+struct TimelineText;
+
+fn spawn_timeline(mut commands: Commands, game_asset_handles: Res<LoadedAssets>) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: PLAYHEAD_COLOR,
+                custom_size: Some(Vec2::new(2.0, TIMELINE_HEIGHT + 6.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(TIMELINE_X, TIMELINE_Y, TEXT_Z + 1.0),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(Playhead)
+        .insert(TimelineElement)
+        .insert(DebugElement);
+
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                String::new(),
+                TextStyle {
+                    font: game_asset_handles.get_handle(FONT),
+                    font_size: 7.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment {
+                vertical: VerticalAlign::Top,
+                horizontal: HorizontalAlign::Left,
+            }),
+            transform: Transform::from_xyz(TIMELINE_X, TIMELINE_Y - 20.0, TEXT_Z),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(TimelineText)
+        .insert(TimelineElement)
+        .insert(DebugElement);
+}
+
+fn toggle_timeline_visibility(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut timeline_visible: ResMut<TimelineVisible>,
+    mut query: Query<&mut Visibility, With<TimelineElement>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::T) {
+        return;
+    }
+
+    **timeline_visible = !**timeline_visible;
+
+    for mut vis in &mut query {
+        vis.is_visible = **timeline_visible
+    }
+}
+
+/// Every level has its own schedule, and the director can change the phase lengths. The segments are
+/// only spawned again when the phases differ from the ones displayed.
+fn rebuild_segments_when_phases_changed(
+    mut commands: Commands,
+    game_asset_handles: Res<LoadedAssets>,
+    timeline_visible: Res<TimelineVisible>,
+    schedule: Option<Res<Schedule>>,
+    mut displayed_phases: Local<Vec<(State, Option<f32>)>>,
+    segment_query: Query<Entity, With<TimelineSegment>>,
+) {
+    let schedule = match schedule {
+        Some(s) => s,
+        None => return,
+    };
+    let phases = schedule
+        .phases()
+        .iter()
+        .map(|p| (p.state(), p.seconds()))
+        .collect::<Vec<_>>();
+
+    if *displayed_phases == phases {
+        return;
+    }
+
+    for entity in &segment_query {
+        commands.entity(entity).despawn();
+    }
+
+    let font = game_asset_handles.get_handle(FONT);
+    let segment_width = TIMELINE_WIDTH / phases.len() as f32;
+
+    for (i, (state, seconds)) in phases.iter().enumerate() {
+        let x = TIMELINE_X + segment_width * (i as f32 + 0.5);
+
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: phase_color(state),
+                    custom_size: Some(Vec2::new(segment_width - 2.0, TIMELINE_HEIGHT)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(x, TIMELINE_Y, TEXT_Z),
+                visibility: Visibility {
+                    is_visible: **timeline_visible,
+                },
+                ..default()
+            })
+            .insert(TimelineSegment)
+            .insert(TimelineElement)
+            .insert(DebugElement);
+
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    format_phase(state, seconds),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 6.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_alignment(TextAlignment {
+                    vertical: VerticalAlign::Top,
+                    horizontal: HorizontalAlign::Center,
+                }),
+                transform: Transform::from_xyz(x, TIMELINE_Y - TIMELINE_HEIGHT, TEXT_Z),
+                visibility: Visibility {
+                    is_visible: **timeline_visible,
+                },
+                ..default()
+            })
+            .insert(TimelineSegment)
+            .insert(TimelineElement)
+            .insert(DebugElement);
+    }
+
+    *displayed_phases = phases;
+}
+
+fn phase_color(state: &State) -> Color {
+    match state {
+        Scatter => SCATTER_COLOR,
+        _ => CHASE_COLOR,
+    }
+}
+
+/// For example "S 7" or "C -" for an infinite chase phase.
+fn format_phase(state: &State, seconds: &Option<f32>) -> String {
+    let initial = match state {
+        Scatter => "S",
+        _ => "C",
+    };
+
+    match seconds {
+        Some(s) => format!("{} {}", initial, s.round()),
+        None => format!("{} -", initial),
+    }
+}
+
+fn update_playhead(
+    schedule: Option<Res<Schedule>>,
+    mut query: Query<&mut Transform, With<Playhead>>,
+) {
+    let schedule = match schedule {
+        Some(s) => s,
+        None => return,
+    };
+    let segment_width = TIMELINE_WIDTH / schedule.phases().len() as f32;
+    let current_phase = &schedule.phases()[schedule.current_phase_index()];
+    let progress = match current_phase.seconds() {
+        Some(seconds) => (schedule.elapsed_in_current_phase() / seconds).min(1.0),
+        None => 0.0,
+    };
+
+    for mut transform in &mut query {
+        transform.translation.x =
+            TIMELINE_X + segment_width * (schedule.current_phase_index() as f32 + progress);
+    }
+}
+
+fn update_timeline_text(
+    schedule: Option<Res<Schedule>>,
+    state_overrides: Res<StateOverrides>,
+    override_selection: Res<OverrideSelection>,
+    ghost_query: Query<(&Ghost, &GhostId)>,
+    mut query: Query<&mut Text, With<TimelineText>>,
+) {
+    let schedule = match schedule {
+        Some(s) => s,
+        None => return,
+    };
+    let current_phase = &schedule.phases()[schedule.current_phase_index()];
+    let phase_text = format!(
+        "Phase {}/{}: {} {:.1}/{} | frozen {:.1}",
+        schedule.current_phase_index() + 1,
+        schedule.phases().len(),
+        current_phase.state(),
+        schedule.elapsed_in_current_phase(),
+        current_phase
+            .seconds()
+            .map_or("-".to_string(), |s| format!("{:.1}", s)),
+        schedule.frozen_time().as_secs_f32()
+    );
+
+    let selection_text = match **override_selection {
+        Some(id) => match ghost_query.iter().find(|(_, ghost_id)| **ghost_id == id) {
+            Some((ghost, _)) => format!("{:?} {}", ghost, id.0),
+            None => format!("ghost {}", id.0),
+        },
+        None => "all".to_string(),
+    };
+    let forced = match **override_selection {
+        Some(id) => state_overrides.of(&id),
+        None => state_overrides.global(),
+    };
+    let override_text = format!(
+        "Override (G) {}: {}",
+        selection_text,
+        forced.map_or("-".to_string(), |s| s.to_string())
+    );
+
+    for mut text in &mut query {
+        text.sections[0].value = format!("{}\n{}", phase_text, override_text)
+    }
+}
+
+/// Cycle from all ghosts through every single ghost and back.
+fn select_ghost_to_override(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut override_selection: ResMut<OverrideSelection>,
+    ghost_query: Query<&GhostId>,
+    lockstep: Option<Res<Lockstep>>,
+) {
+    if lockstep.is_some() || !keyboard_input.just_pressed(KeyCode::G) {
+        return;
+    }
+
+    let mut ids = ghost_query.iter().copied().collect::<Vec<_>>();
+    ids.sort();
+
+    **override_selection = match **override_selection {
+        None => ids.first().copied(),
+        Some(selected) => ids.into_iter().find(|id| *id > selected),
+    };
+}
+
+fn override_states_on_key_press(
+    keyboard_input: Res<Input<KeyCode>>,
+    override_selection: Res<OverrideSelection>,
+    mut state_overrides: ResMut<StateOverrides>,
+    lockstep: Option<Res<Lockstep>>,
+) {
+    if lockstep.is_some() {
+        return;
+    }
+
+    let state = if keyboard_input.just_pressed(KeyCode::F5) {
+        Scatter
+    } else if keyboard_input.just_pressed(KeyCode::F6) {
+        Chase
+    } else if keyboard_input.just_pressed(KeyCode::F7) {
+        Frightened
+    } else {
+        if keyboard_input.just_pressed(KeyCode::F8) {
+            state_overrides.clear()
+        }
+        return;
+    };
+
+    match **override_selection {
+        Some(id) => state_overrides.force(id, state),
+        None => state_overrides.force_all(state),
+    }
+}
@@ -0,0 +1,171 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::board_dimensions::BoardDimensions;
+use crate::constants::DEBUG_OVERLAY_Z;
+use crate::debug::{ghost_color, DebugElement};
+use crate::ghosts::brain::AimHint;
+use crate::ghosts::state::State;
+use crate::ghosts::state::State::*;
+use crate::ghosts::target::Target;
+use crate::ghosts::Ghost;
+
+const AIM_ALPHA: f32 = 0.4;
+const LINE_WIDTH: f32 = 1.5;
+const CIRCLE_DOTS: usize = 48;
+
+/// Shows where the ghosts are heading.
+///
+/// 'O' toggles the overlay. Every ghost in chase or scatter marks the field his brain aims at with a transparent
+/// square in his color, the next field he chose on the way there gets a small solid square. The brain can explain
+/// its aim with further shapes: for inky, a line is drawn from blinky through the field in front of capman (marked
+/// with a small square) to his aim. Clyde in chase gets a circle with the radius of 8 fields, in which he gets
+/// scared of capman.
+pub(in crate::debug) struct TargetDebugPlugin;
+
+impl Plugin for TargetDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OverlayVisible>()
+            .add_system(toggle_overlay_visibility)
+            .add_system(draw_overlay);
+    }
+}
+
+#[derive(Default, Deref, DerefMut)]
+struct OverlayVisible(bool);
+
+#[derive(Component)]
+struct OverlayElement;
+
+fn toggle_overlay_visibility(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut overlay_visible: ResMut<OverlayVisible>,
+) {
+    if keyboard_input.just_pressed(KeyCode::O) {
+        **overlay_visible = !**overlay_visible;
+    }
+}
+
+/// Targets change every few frames, so the overlay redraws every frame. It reuses its sprites and only spawns new
+/// ones if it needs more than ever before. The sprites it does not need are hidden.
+fn draw_overlay(
+    mut commands: Commands,
+    overlay_visible: Res<OverlayVisible>,
+    dimensions: Option<Res<BoardDimensions>>,
+    mut overlay_query: Query<(&mut Sprite, &mut Transform, &mut Visibility), With<OverlayElement>>,
+    ghost_query: Query<(&Ghost, &State, &Target, &Transform), Without<OverlayElement>>,
+) {
+    let shapes = match dimensions {
+        Some(d) if **overlay_visible => overlay_shapes(&d, &ghost_query),
+        _ => vec![],
+    };
+    let mut shapes = shapes.into_iter();
+
+    for (mut sprite, mut transform, mut visibility) in &mut overlay_query {
+        match shapes.next() {
+            Some((new_sprite, new_transform)) => {
+                *sprite = new_sprite;
+                *transform = new_transform;
+                visibility.is_visible = true;
+            }
+            None if visibility.is_visible => visibility.is_visible = false,
+            None => (),
+        }
+    }
+
+    for (sprite, transform) in shapes {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite,
+                transform,
+                ..default()
+            })
+            .insert(OverlayElement)
+            .insert(DebugElement);
+    }
+}
+
+fn overlay_shapes(
+    dimensions: &BoardDimensions,
+    ghost_query: &Query<(&Ghost, &State, &Target, &Transform), Without<OverlayElement>>,
+) -> Vec<(Sprite, Transform)> {
+    let field = dimensions.field();
+    let mut shapes = vec![];
+
+    for (ghost, state, target, transform) in ghost_query {
+        let color = ghost_color(ghost);
+
+        if target.is_set() {
+            shapes.push(square(target.get(), field * 0.4, color));
+        }
+
+        let aim = match target.aim() {
+            Some(aim) if matches!(state, Chase | Scatter) => aim,
+            _ => continue,
+        };
+        shapes.push(square(
+            dimensions.pos_to_vec(&aim, 0.0),
+            field,
+            *color.clone().set_a(AIM_ALPHA),
+        ));
+
+        for hint in target.hints() {
+            match *hint {
+                AimHint::Field(position) => shapes.push(square(
+                    dimensions.pos_to_vec(&position, 0.0),
+                    field * 0.4,
+                    color,
+                )),
+                AimHint::Line(from, to) => shapes.push(line(
+                    dimensions.pos_to_vec(&from, 0.0),
+                    dimensions.pos_to_vec(&to, 0.0),
+                    color,
+                )),
+                AimHint::Radius(fields) => shapes.extend(
+                    circle(transform.translation, field * fields, CIRCLE_DOTS)
+                        .map(|point| square(point, LINE_WIDTH * 2.0, color)),
+                ),
+            }
+        }
+    }
+
+    shapes
+}
+
+fn square(center: Vec3, size: f32, color: Color) -> (Sprite, Transform) {
+    let sprite = Sprite {
+        color,
+        custom_size: Some(Vec2::splat(size)),
+        ..default()
+    };
+    (
+        sprite,
+        Transform::from_translation(center.truncate().extend(DEBUG_OVERLAY_Z)),
+    )
+}
+
+/// A thin sprite from one point to another, rotated along the way.
+fn line(from: Vec3, to: Vec3, color: Color) -> (Sprite, Transform) {
+    let diff = (to - from).truncate();
+    let center = (from + to).truncate() / 2.0;
+
+    let sprite = Sprite {
+        color,
+        custom_size: Some(Vec2::new(diff.length(), LINE_WIDTH)),
+        ..default()
+    };
+    let transform = Transform {
+        translation: center.extend(DEBUG_OVERLAY_Z),
+        rotation: Quat::from_rotation_z(diff.y.atan2(diff.x)),
+        ..default()
+    };
+    (sprite, transform)
+}
+
+fn circle(center: Vec3, radius: f32, points: usize) -> impl Iterator<Item = Vec3> {
+    (0..points).map(move |i| {
+        let angle = TAU * i as f32 / points as f32;
+        center + Vec3::new(angle.cos(), angle.sin(), 0.0) * radius
+    })
+}
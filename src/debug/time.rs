@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+use crate::clock::TimeScale;
+use crate::constants::{FONT, TEXT_Z, WINDOW_WIDTH};
+use crate::debug::inspector::InspectorApp;
+use crate::debug::DebugElement;
+use crate::game_assets::loaded_assets::LoadedAssets;
+use crate::life_cycle::LifeCycle::Loading;
+use crate::network::Lockstep;
+
+const MIN_SCALE: f32 = 1.0 / 16.0;
+const MAX_SCALE: f32 = 8.0;
+const INDICATOR_COLOR: Color = Color::rgb(1.0, 1.0, 0.0);
+
+/// Slows down, speeds up, pauses and steps the game.
+///
+/// 'P' pauses and resumes, 'N' lets a single frame pass while paused. '-' halves the speed, '=' doubles it and '0'
+/// resets it. The current speed is shown in the bottom right corner as long as it differs from the normal one.
+///
+/// In a network game, the time cannot be controlled, as the other instance would run out of sync.
+pub(in crate::debug) struct TimeDebugPlugin;
+
+impl Plugin for TimeDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeScale>()
+            .add_system_set(SystemSet::on_exit(Loading).with_system(spawn_indicator))
+            .add_system(control_time_on_key_press)
+            .add_system(update_indicator)
+            .add_watch(
+                "Game",
+                "Speed",
+                |time_scale: Res<TimeScale>| match time_scale.is_paused() {
+                    true => format!("x{} (paused)", time_scale.scale()),
+                    false => format!("x{}", time_scale.scale()),
+                },
+            );
+    }
+}
+
+#[derive(Component)]
+struct TimeIndicator;
+
+fn spawn_indicator(mut commands: Commands, game_asset_handles: Res<LoadedAssets>) {
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                String::new(),
+                TextStyle {
+                    font: game_asset_handles.get_handle(FONT),
+                    font_size: 10.0,
+                    color: INDICATOR_COLOR,
+                },
+            )
+            .with_alignment(TextAlignment {
+                vertical: VerticalAlign::Bottom,
+                horizontal: HorizontalAlign::Right,
+            }),
+            transform: Transform::from_xyz(WINDOW_WIDTH - 10.0, 10.0, TEXT_Z),
+            ..default()
+        })
+        .insert(TimeIndicator)
+        .insert(DebugElement);
+}
+
+fn control_time_on_key_press(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut time_scale: ResMut<TimeScale>,
+    lockstep: Option<Res<Lockstep>>,
+) {
+    if lockstep.is_some() {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::P) {
+        time_scale.toggle_pause()
+    }
+
+    if keyboard_input.just_pressed(KeyCode::N) {
+        time_scale.step()
+    }
+
+    let scale = time_scale.scale();
+    if keyboard_input.just_pressed(KeyCode::Minus) {
+        time_scale.set_scale((scale / 2.0).max(MIN_SCALE))
+    } else if keyboard_input.just_pressed(KeyCode::Equals) {
+        time_scale.set_scale((scale * 2.0).min(MAX_SCALE))
+    } else if keyboard_input.just_pressed(KeyCode::Key0) {
+        time_scale.set_scale(1.0)
+    }
+}
+
+fn update_indicator(time_scale: Res<TimeScale>, mut query: Query<&mut Text, With<TimeIndicator>>) {
+    for mut text in &mut query {
+        text.sections[0].value = format_time_scale(&time_scale)
+    }
+}
+
+/// For example "x0.5" or "PAUSED x0.5 (N: next frame)". Empty when the game runs normally.
+fn format_time_scale(time_scale: &TimeScale) -> String {
+    let mut parts = vec![];
+
+    if time_scale.is_paused() {
+        parts.push("PAUSED".to_string())
+    }
+    if time_scale.scale() != 1.0 {
+        parts.push(format!("x{}", time_scale.scale()))
+    }
+    if time_scale.is_paused() {
+        parts.push("(N: next frame)".to_string())
+    }
+
+    parts.join(" ")
+}
@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::board_dimensions::BoardDimensions;
+use crate::constants::FONT;
+use crate::game_assets::loaded_assets::LoadedAssets;
+use crate::ghosts::brain::BrainKind;
+use crate::life_cycle::LifeCycle::Start;
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Difficulty::from_args())
+            .add_system_set(SystemSet::on_enter(Start).with_system(spawn_difficulty_text));
+    }
+}
+
+/// Resource for the difficulty preset the game is played with.
+///
+/// The specs per level and the ghost schedules describe the arcade game. Every other preset scales them
+/// when the game starts.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Arcade,
+    Hard,
+    Nightmare,
+}
+
+impl Difficulty {
+    /// Passing '--difficulty easy|arcade|hard|nightmare' on the command line selects the preset. Arcade is the default.
+    fn from_args() -> Self {
+        let args = std::env::args().collect::<Vec<_>>();
+
+        match args
+            .iter()
+            .position(|arg| arg == "--difficulty")
+            .and_then(|i| args.get(i + 1))
+            .map(|value| value.to_lowercase())
+            .as_deref()
+        {
+            Some("easy") => Difficulty::Easy,
+            Some("hard") => Difficulty::Hard,
+            Some("nightmare") => Difficulty::Nightmare,
+            _ => Difficulty::Arcade,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "EASY",
+            Difficulty::Arcade => "ARCADE",
+            Difficulty::Hard => "HARD",
+            Difficulty::Nightmare => "NIGHTMARE",
+        }
+    }
+
+    /// Multiplied with every ghost speed modifier.
+    pub fn ghost_speed_factor(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.9,
+            Difficulty::Arcade => 1.0,
+            Difficulty::Hard => 1.05,
+            Difficulty::Nightmare => 1.1,
+        }
+    }
+
+    /// Multiplied with the time ghosts stay frightened.
+    pub fn frightened_time_factor(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Arcade => 1.0,
+            Difficulty::Hard => 0.6,
+            Difficulty::Nightmare => 0.3,
+        }
+    }
+
+    /// Multiplied with the number of remaining dots that make blinky an elroy. More dots means an earlier elroy.
+    pub fn elroy_dots_factor(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Arcade => 1.0,
+            Difficulty::Hard => 1.5,
+            Difficulty::Nightmare => 2.0,
+        }
+    }
+
+    /// Multiplied with the length of every scatter phase.
+    pub fn scatter_time_factor(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Arcade => 1.0,
+            Difficulty::Hard => 0.7,
+            Difficulty::Nightmare => 0.4,
+        }
+    }
+
+    /// Multiplied with the time after which the ghost house releases a ghost if capman stops eating dots.
+    pub fn release_timer_factor(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Arcade => 1.0,
+            Difficulty::Hard => 0.75,
+            Difficulty::Nightmare => 0.5,
+        }
+    }
+
+    /// The brain the ghosts get with this preset, if it should replace the selected one.
+    pub fn brain_kind(&self) -> Option<BrainKind> {
+        match self {
+            Difficulty::Easy | Difficulty::Arcade => None,
+            Difficulty::Hard | Difficulty::Nightmare => Some(BrainKind::Pathfinding),
+        }
+    }
+}
+
+/// The selected difficulty is displayed centered above the board, below the title.
+fn spawn_difficulty_text(
+    mut commands: Commands,
+    game_asset_handles: Res<LoadedAssets>,
+    difficulty: Res<Difficulty>,
+    dimensions: Res<BoardDimensions>,
+) {
+    let origin = dimensions.origin();
+
+    commands.spawn_bundle(Text2dBundle {
+        text: Text::from_section(
+            difficulty.name().to_string(),
+            TextStyle {
+                font: game_asset_handles.get_handle(FONT),
+                font_size: 10.0,
+                color: Color::rgb(1.0, 1.0, 1.0),
+            },
+        )
+        .with_alignment(TextAlignment {
+            vertical: VerticalAlign::Center,
+            horizontal: HorizontalAlign::Center,
+        }),
+        transform: Transform::from_xyz(
+            origin.x + dimensions.board_width() / 2.0,
+            origin.y + dimensions.board_height(),
+            0.0,
+        ),
+        ..Default::default()
+    });
+}
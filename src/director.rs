@@ -0,0 +1,378 @@
+use std::fmt::{Display, Formatter};
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::capman::{Capman, Dying};
+use crate::ghosts::schedule::{Schedule, ScheduleByLevel};
+use crate::ghosts::state::State::Scatter;
+use crate::interactions::{EGhostEaten, LCapmanInteractions};
+use crate::level::Level;
+use crate::life_cycle::LifeCycle::*;
+use crate::specs_per_level::{Spec, SpecsPerLevel};
+
+/// A level cleared faster than this many seconds counts as cleared fast.
+const PAR_TIME: f32 = 90.0;
+
+/// Eating at least this many ghosts in one level counts as mastering the energizers.
+const GHOST_HUNTER_THRESHOLD: usize = 8;
+
+/// Lets the game adapt its difficulty to the performance of the players.
+///
+/// The director watches how often capman dies, how fast the levels get cleared and how many ghosts get eaten.
+/// Whenever a capman dies, it gets a bit easier. When a level was cleared, it might get harder. Every
+/// adjustment stays within fixed bounds and is logged, so designers can audit what the director did.
+///
+/// Start the game with '--adaptive' to enable it. The director and the performance only exist when it is
+/// enabled, every system does nothing otherwise.
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        set_adaptive(
+            &mut app.world,
+            std::env::args().any(|arg| arg == "--adaptive"),
+        );
+
+        app.add_system_set(
+            SystemSet::on_update(Running)
+                .with_system(track_time)
+                .with_system(track_eaten_ghosts)
+                .after(LCapmanInteractions),
+        )
+        .add_system(ease_when_capman_started_dying)
+        .add_system_set(
+            SystemSet::on_enter(LevelTransition).with_system(adjust_after_cleared_level),
+        );
+    }
+}
+
+/// Whether the director adapts the difficulty of this game.
+pub fn is_adaptive(world: &World) -> bool {
+    world.contains_resource::<Director>()
+}
+
+/// Enable or disable the director. Must happen before the game starts, so no adjustment gets lost.
+pub fn set_adaptive(world: &mut World, adaptive: bool) {
+    if adaptive {
+        world.insert_resource(Director::default());
+        world.insert_resource(Performance::default());
+    } else {
+        world.remove_resource::<Director>();
+        world.remove_resource::<Performance>();
+    }
+}
+
+/// How the players performed in the current level.
+#[derive(Debug, Default)]
+pub struct Performance {
+    seconds: f32,
+    deaths: usize,
+    eaten_ghosts: usize,
+}
+
+/// Resource which knows the current adjustments and every change made to them.
+struct Director {
+    ghost_speed: Adjustable,
+    frightened_time: Adjustable,
+    scatter_time: Adjustable,
+    /// The specs before the director touched them, so adjustments do not stack up.
+    original_specs: HashMap<usize, Spec>,
+    /// The schedules before the director touched them.
+    original_schedules: HashMap<usize, Schedule>,
+    log: Vec<Adjustment>,
+}
+
+impl Default for Director {
+    fn default() -> Self {
+        Director {
+            ghost_speed: Adjustable::new("ghost speed", 0.05, 0.85, 1.15),
+            frightened_time: Adjustable::new("frightened time", 0.1, 0.5, 1.5),
+            scatter_time: Adjustable::new("scatter time", 0.1, 0.5, 1.5),
+            original_specs: HashMap::default(),
+            original_schedules: HashMap::default(),
+            log: vec![],
+        }
+    }
+}
+
+/// A factor the director can move in steps, but never beyond its bounds.
+struct Adjustable {
+    name: &'static str,
+    value: f32,
+    step: f32,
+    min: f32,
+    max: f32,
+}
+
+impl Adjustable {
+    fn new(name: &'static str, step: f32, min: f32, max: f32) -> Self {
+        Adjustable {
+            name,
+            value: 1.0,
+            step,
+            min,
+            max,
+        }
+    }
+
+    /// Move the value the given number of steps up (positive) or down (negative).
+    ///
+    /// Returns the adjustment, or None if the value was already at its bound.
+    fn adjust(&mut self, steps: f32, level: usize, reason: &'static str) -> Option<Adjustment> {
+        let from = self.value;
+        self.value = (self.value + steps * self.step).clamp(self.min, self.max);
+
+        match (self.value - from).abs() > f32::EPSILON {
+            true => Some(Adjustment {
+                level,
+                name: self.name,
+                from,
+                to: self.value,
+                reason,
+            }),
+            false => None,
+        }
+    }
+}
+
+/// One change the director made.
+#[derive(Debug)]
+struct Adjustment {
+    level: usize,
+    name: &'static str,
+    from: f32,
+    to: f32,
+    reason: &'static str,
+}
+
+impl Display for Adjustment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "level {}: {} {:.2} -> {:.2} ({})",
+            self.level, self.name, self.from, self.to, self.reason
+        )
+    }
+}
+
+impl Director {
+    /// Capman died, so the ghosts get slower, stay frightened longer and scatter longer.
+    fn ease(&mut self, level: usize) {
+        let reason = "capman died";
+        let adjustments = [
+            self.ghost_speed.adjust(-1.0, level, reason),
+            self.frightened_time.adjust(1.0, level, reason),
+            self.scatter_time.adjust(1.0, level, reason),
+        ];
+        self.record(adjustments)
+    }
+
+    /// The level was cleared, so it gets harder where the players did well.
+    fn challenge(&mut self, level: usize, performance: &Performance) {
+        let adjustments = [
+            match performance.deaths {
+                0 => self
+                    .ghost_speed
+                    .adjust(1.0, level, "level cleared without dying"),
+                _ => None,
+            },
+            match performance.seconds < PAR_TIME {
+                true => self.scatter_time.adjust(-1.0, level, "level cleared fast"),
+                false => None,
+            },
+            match performance.eaten_ghosts >= GHOST_HUNTER_THRESHOLD {
+                true => self
+                    .frightened_time
+                    .adjust(-1.0, level, "many ghosts eaten"),
+                false => None,
+            },
+        ];
+        self.record(adjustments)
+    }
+
+    fn record(&mut self, adjustments: impl IntoIterator<Item = Option<Adjustment>>) {
+        for adjustment in adjustments.into_iter().flatten() {
+            info!("Adaptive difficulty: {}", adjustment);
+            self.log.push(adjustment)
+        }
+    }
+
+    /// Write the adjusted spec for the given level, based on its original spec.
+    fn apply_to_spec(&mut self, level: usize, specs_per_level: &mut SpecsPerLevel) {
+        let original = self
+            .original_specs
+            .entry(level)
+            .or_insert_with(|| specs_per_level.get_for(&Level(level)).clone())
+            .clone();
+
+        specs_per_level.set_for(
+            Level(level),
+            Spec {
+                ghost_normal_speed_modifier: original.ghost_normal_speed_modifier
+                    * self.ghost_speed.value,
+                ghost_tunnel_speed_modifier: original.ghost_tunnel_speed_modifier
+                    * self.ghost_speed.value,
+                ghost_frightened_speed_modifier: original.ghost_frightened_speed_modifier
+                    * self.ghost_speed.value,
+                elroy_1_speed_modifier: original.elroy_1_speed_modifier * self.ghost_speed.value,
+                elroy_2_speed_modifier: original.elroy_2_speed_modifier * self.ghost_speed.value,
+                frightened_time: original.frightened_time * self.frightened_time.value,
+                ..original
+            },
+        )
+    }
+
+    /// Write the adjusted schedule for the given level, based on its original schedule.
+    fn apply_to_schedule(&mut self, level: usize, schedule_by_level: &mut ScheduleByLevel) {
+        let mut schedule = self
+            .original_schedules
+            .entry(level)
+            .or_insert_with(|| schedule_by_level.get_schedule_for_level(&Level(level)))
+            .clone();
+
+        schedule.scale_phases(Scatter, self.scatter_time.value);
+        schedule_by_level.set_schedule_for_level(Level(level), schedule)
+    }
+}
+
+fn track_time(time: Res<Time>, performance: Option<ResMut<Performance>>) {
+    if let Some(mut performance) = performance {
+        performance.seconds += time.delta_seconds()
+    }
+}
+
+fn track_eaten_ghosts(
+    mut event_reader: EventReader<EGhostEaten>,
+    performance: Option<ResMut<Performance>>,
+) {
+    let eaten_ghosts = event_reader.iter().count();
+
+    if let Some(mut performance) = performance {
+        performance.eaten_ghosts += eaten_ghosts
+    }
+}
+
+/// Every capman that starts dying counts as one death, no matter if he dies alone or together with the
+/// others. The current level goes on, so its spec and the running schedule get adjusted.
+///
+/// Capmen die while the game is running and while it is dying, so this runs in every state. The schedule
+/// only exists once the game started.
+fn ease_when_capman_started_dying(
+    level: Res<Level>,
+    director: Option<ResMut<Director>>,
+    performance: Option<ResMut<Performance>>,
+    mut specs_per_level: ResMut<SpecsPerLevel>,
+    mut schedule: Option<ResMut<Schedule>>,
+    query: Query<(), (With<Capman>, Added<Dying>)>,
+) {
+    let (mut director, mut performance) = match (director, performance) {
+        (Some(director), Some(performance)) => (director, performance),
+        _ => return,
+    };
+
+    for _ in &query {
+        let scatter_time_before = director.scatter_time.value;
+        performance.deaths += 1;
+        director.ease(**level);
+        director.apply_to_spec(**level, &mut specs_per_level);
+
+        if let Some(schedule) = schedule.as_mut() {
+            schedule.scale_phases(Scatter, director.scatter_time.value / scatter_time_before);
+        }
+    }
+}
+
+/// The level is increased when the transition is over, so the adjustments are made for the next level.
+fn adjust_after_cleared_level(
+    level: Res<Level>,
+    director: Option<ResMut<Director>>,
+    performance: Option<ResMut<Performance>>,
+    mut specs_per_level: ResMut<SpecsPerLevel>,
+    mut schedule_by_level: ResMut<ScheduleByLevel>,
+) {
+    let (mut director, mut performance) = match (director, performance) {
+        (Some(director), Some(performance)) => (director, performance),
+        _ => return,
+    };
+
+    director.challenge(**level, &performance);
+    director.apply_to_spec(**level + 1, &mut specs_per_level);
+    director.apply_to_schedule(**level + 1, &mut schedule_by_level);
+    *performance = Performance::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::capman::{Capman, Dying};
+    use crate::director::{ease_when_capman_started_dying, Director, Performance, PAR_TIME};
+    use crate::level::Level;
+    use crate::specs_per_level::SpecsPerLevel;
+
+    #[test]
+    fn a_lost_life_makes_the_game_easier() {
+        let mut director = Director::default();
+
+        director.ease(1);
+
+        assert!(director.ghost_speed.value < 1.0);
+        assert!(director.frightened_time.value > 1.0);
+        assert!(director.scatter_time.value > 1.0);
+        assert_eq!(director.log.len(), 3);
+    }
+
+    #[test]
+    fn only_good_performance_makes_the_game_harder() {
+        let mut director = Director::default();
+
+        director.challenge(
+            1,
+            &Performance {
+                seconds: PAR_TIME * 2.0,
+                deaths: 0,
+                eaten_ghosts: 0,
+            },
+        );
+
+        assert!(director.ghost_speed.value > 1.0);
+        assert_eq!(director.scatter_time.value, 1.0);
+        assert_eq!(director.frightened_time.value, 1.0);
+        assert_eq!(director.log.len(), 1);
+    }
+
+    #[test]
+    fn adjustments_stay_within_their_bounds() {
+        let mut director = Director::default();
+
+        for level in 0..100 {
+            director.ease(level);
+        }
+
+        assert_eq!(director.ghost_speed.value, director.ghost_speed.min);
+        assert_eq!(director.frightened_time.value, director.frightened_time.max);
+        assert!(director.log.iter().all(|a| a.to >= 0.5 && a.to <= 1.5));
+    }
+
+    #[test]
+    fn every_capman_that_starts_dying_counts_as_one_death() {
+        let mut app = App::new();
+        app.insert_resource(Level(1))
+            .insert_resource(Director::default())
+            .insert_resource(Performance::default())
+            .insert_resource(SpecsPerLevel::arcade())
+            .add_system(ease_when_capman_started_dying);
+        let capmen = [(); 2].map(|_| app.world.spawn().insert(Capman).id());
+
+        app.world.entity_mut(capmen[0]).insert(Dying);
+        app.update();
+        app.update();
+        assert_eq!(app.world.resource::<Performance>().deaths, 1);
+
+        app.world.entity_mut(capmen[1]).insert(Dying);
+        app.update();
+        assert_eq!(app.world.resource::<Performance>().deaths, 2);
+        assert_eq!(app.world.resource::<Director>().log.len(), 6);
+    }
+}
@@ -119,6 +119,7 @@ fn play_waka_when_dot_was_eaten(
 #[derive(Component)]
 pub struct Dot;
 
+#[derive(Debug)]
 pub struct EatenDots {
     max: usize,
     eaten: usize,
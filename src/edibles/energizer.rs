@@ -5,7 +5,8 @@ use std::time::Duration;
 use crate::constants::ENERGIZER_Z;
 use crate::edibles::Edible;
 use crate::game_assets::loaded_assets::LoadedAssets;
-use crate::interactions::EEnergizerEaten;
+use crate::ghosts::state::StateSetter;
+use crate::interactions::{EEnergizerEaten, LCapmanInteractions};
 use crate::is;
 use crate::level::Level;
 use crate::life_cycle::LifeCycle::*;
@@ -24,7 +25,10 @@ impl Plugin for EnergizerPlugin {
                     .with_system(start_energizer_timer_when_energizer_eaten)
                     .with_system(
                         update_energizer_timer.after(start_energizer_timer_when_energizer_eaten),
-                    ),
+                    )
+                    .label(LEnergizerTimer)
+                    .after(LCapmanInteractions)
+                    .before(StateSetter),
             )
             .add_system_set(SystemSet::on_exit(LevelTransition).with_system(spawn_energizer));
     }
@@ -38,6 +42,11 @@ pub struct Energizer;
 #[derive(Copy, Clone)]
 pub struct EnergizerOver;
 
+/// Marks the systems that run the energizer timer. The ghosts' state follows them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct LEnergizerTimer;
+
+#[derive(Debug)]
 pub struct EnergizerTimer {
     timer: Timer,
 }
@@ -89,6 +98,7 @@ fn spawn_energizer(
     }
 }
 
+/// On levels without frightened time, the ghosts only reverse, so there is no timer.
 fn start_energizer_timer_when_energizer_eaten(
     mut commands: Commands,
     mut event_reader: EventReader<EEnergizerEaten>,
@@ -97,7 +107,10 @@ fn start_energizer_timer_when_energizer_eaten(
 ) {
     for _ in event_reader.iter() {
         let spec = specs_per_level.get_for(&level);
-        commands.insert_resource(EnergizerTimer::start(spec.frightened_time));
+
+        if spec.frightened_time > 0.0 {
+            commands.insert_resource(EnergizerTimer::start(spec.frightened_time));
+        }
     }
 }
 
@@ -116,3 +129,49 @@ fn update_energizer_timer(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::edibles::energizer::{start_energizer_timer_when_energizer_eaten, EnergizerTimer};
+    use crate::interactions::EEnergizerEaten;
+    use crate::level::Level;
+    use crate::specs_per_level::{Spec, SpecsPerLevel};
+
+    fn eat_energizer_with_frightened_time(frightened_time: f32) -> App {
+        let mut specs_per_level = SpecsPerLevel::arcade();
+        specs_per_level.set_for(
+            Level(1),
+            Spec {
+                frightened_time,
+                ..specs_per_level.get_for(&Level(1)).clone()
+            },
+        );
+
+        let mut app = App::new();
+        app.add_event::<EEnergizerEaten>()
+            .insert_resource(Level(1))
+            .insert_resource(specs_per_level)
+            .add_system(start_energizer_timer_when_energizer_eaten);
+        let capman = app.world.spawn().id();
+        app.world.send_event(EEnergizerEaten(capman));
+        app.update();
+        app
+    }
+
+    #[test]
+    fn an_eaten_energizer_starts_the_frightened_time() {
+        let app = eat_energizer_with_frightened_time(6.0);
+
+        let timer = app.world.get_resource::<EnergizerTimer>();
+        assert_eq!(timer.map(EnergizerTimer::remaining), Some(6.0));
+    }
+
+    #[test]
+    fn without_frightened_time_the_ghosts_only_reverse() {
+        let app = eat_energizer_with_frightened_time(0.0);
+
+        assert!(app.world.get_resource::<EnergizerTimer>().is_none());
+    }
+}
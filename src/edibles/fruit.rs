@@ -1,3 +1,4 @@
+use std::str::FromStr;
 use std::time::Duration;
 use bevy::prelude::*;
 use crate::level::Level;
@@ -7,7 +8,7 @@ use crate::constants::FRUIT_Z;
 use crate::edibles::dots::EatenDots;
 use crate::edibles::Edible;
 use crate::game_assets::loaded_assets::LoadedAssets;
-use crate::interactions::{EDotEaten, EFruitEaten};
+use crate::interactions::{EDotEaten, EFruitEaten, LCapmanInteractions};
 use crate::is;
 use crate::life_cycle::LifeCycle::{LevelTransition, Ready, Running};
 use crate::map::{Element, Map};
@@ -20,9 +21,9 @@ impl Plugin for FruitPlugin {
         app
             .add_system_set(
                 SystemSet::on_update(Running)
-                    .with_system(spawn_fruit_when_dot_limit_reached)
+                    .with_system(spawn_fruit_when_dot_limit_reached.after(LCapmanInteractions))
                     .with_system(update_despawn_timer)
-                    .with_system(despawn_fruit_if_timer_exceeded)
+                    .with_system(despawn_fruit_if_timer_exceeded.after(update_despawn_timer))
                     .with_system(play_fruit_eaten_sound_when_fruit_was_eaten)
                     .with_system(reset_fruit_despawn_timer_when_level_changed)
             )
@@ -52,7 +53,26 @@ pub enum Fruit {
     Key,
 }
 
-#[derive(Deref, DerefMut)]
+impl FromStr for Fruit {
+    type Err = String;
+
+    /// Parse the name of the fruit, like "Key".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Cherry" => Ok(Cherry),
+            "Strawberry" => Ok(Strawberry),
+            "Peach" => Ok(Peach),
+            "Apple" => Ok(Apple),
+            "Grapes" => Ok(Grapes),
+            "Galaxian" => Ok(Galaxian),
+            "Bell" => Ok(Bell),
+            "Key" => Ok(Key),
+            _ => Err(format!("unknown fruit '{}'", s)),
+        }
+    }
+}
+
+#[derive(Debug, Deref, DerefMut)]
 pub struct FruitDespawnTimer(Timer);
 
 impl FruitDespawnTimer {
@@ -80,28 +100,39 @@ fn spawn_fruit_when_dot_limit_reached(
 
     for _ in event_reader.iter() {
         if let 70 | 170 = num_eaten_dots {
-            let transform = dimensions.positions_to_trans(map.get_positions_matching(is!(Element::FruitSpawn)), FRUIT_Z);
-            let dimension = Vec2::new(dimensions.fruit(), dimensions.fruit());
             let fruit = specs_per_level.get_for(&level).fruit_to_spawn;
-
-            commands.spawn()
-                .insert_bundle(SpriteBundle {
-                    texture: get_texture_for_fruit(&fruit, &loaded_assets),
-                    sprite: Sprite {
-                        custom_size: Some(dimension),
-                        ..default()
-                    },
-                    transform,
-                    ..Default::default()
-                })
-                .insert(fruit)
-                .insert(Edible)
-            ;
-            commands.insert_resource(FruitDespawnTimer::new());
+            spawn_fruit(&mut commands, &loaded_assets, &map, &dimensions, fruit);
         }
     }
 }
 
+/// Spawn the given fruit on the fruit spawn and start its despawn timer.
+pub fn spawn_fruit(
+    commands: &mut Commands,
+    loaded_assets: &LoadedAssets,
+    map: &Map,
+    dimensions: &BoardDimensions,
+    fruit: Fruit,
+) {
+    let transform = dimensions.positions_to_trans(map.get_positions_matching(is!(Element::FruitSpawn)), FRUIT_Z);
+    let dimension = Vec2::new(dimensions.fruit(), dimensions.fruit());
+
+    commands.spawn()
+        .insert_bundle(SpriteBundle {
+            texture: get_texture_for_fruit(&fruit, loaded_assets),
+            sprite: Sprite {
+                custom_size: Some(dimension),
+                ..default()
+            },
+            transform,
+            ..Default::default()
+        })
+        .insert(fruit)
+        .insert(Edible)
+    ;
+    commands.insert_resource(FruitDespawnTimer::new());
+}
+
 /// Update the despawn timer with delta time.
 fn update_despawn_timer(
     time: Res<Time>,
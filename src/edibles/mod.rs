@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use crate::edibles::dots::DotPlugin;
 use crate::edibles::energizer::EnergizerPlugin;
 use crate::edibles::fruit::FruitPlugin;
+use crate::interactions::LCapmanInteractions;
 use crate::life_cycle::LifeCycle::Running;
 
 pub mod dots;
@@ -18,7 +19,7 @@ impl Plugin for EdiblePlugin {
             .add_plugin(EnergizerPlugin)
             .add_plugin(FruitPlugin)
             .add_system_set(
-                SystemSet::on_update(Running).with_system(check_if_all_edibles_eaten)
+                SystemSet::on_update(Running).with_system(check_if_all_edibles_eaten.before(LCapmanInteractions))
             )
         ;
     }
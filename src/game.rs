@@ -0,0 +1,90 @@
+use bevy::app::PluginGroupBuilder;
+use bevy::prelude::*;
+
+use crate::animation::AnimationPlugin;
+use crate::capman::CapmanPlugin;
+use crate::clock::ClockPlugin;
+use crate::difficulty::DifficultyPlugin;
+use crate::director::DirectorPlugin;
+use crate::edibles::EdiblePlugin;
+use crate::game_assets::GameAssetsPlugin;
+use crate::game_over_screen::GameOverScreenPlugin;
+use crate::ghost_corners::GhostCornersPlugin;
+use crate::ghost_house::GhostHousePlugin;
+use crate::ghost_house_gate::GhostHouseGatePlugin;
+use crate::ghosts::GhostPlugin;
+use crate::high_scores::HighScoresPlugin;
+use crate::interactions::InteractionsPlugin;
+use crate::level::LevelPlugin;
+use crate::life_cycle::GameStatePlugin;
+use crate::lives::LivesPlugin;
+use crate::map::MapPlugin;
+use crate::network::NetworkPlugin;
+use crate::players::PlayersPlugin;
+use crate::random::RandomPlugin;
+use crate::ready_screen::ReadyScreenPlugin;
+use crate::score::ScorePlugin;
+use crate::specs_per_level::SpecsPerLevelPlugin;
+use crate::speed::SpeedPlugin;
+use crate::sprite_sheet::SpriteSheetPlugin;
+use crate::tunnels::TunnelPlugin;
+use crate::walls::WallsPlugin;
+
+/// Every plugin of the game itself, without the camera, the sounds and the debug tools.
+///
+/// The plugins need the asset server, so they have to be added after bevy's plugins. Networked games run exactly
+/// these plugins on both instances, which is why tests use them to check that two instances stay in sync.
+pub struct GamePlugins;
+
+impl PluginGroup for GamePlugins {
+    fn build(&mut self, group: &mut PluginGroupBuilder) {
+        group
+            .add(GameStatePlugin)
+            .add(GameAssetsPlugin)
+            .add(AnimationPlugin)
+            .add(MapPlugin)
+            .add(PlayersPlugin)
+            .add(WallsPlugin)
+            .add(EdiblePlugin)
+            .add(GhostHousePlugin)
+            .add(GhostCornersPlugin)
+            .add(CapmanPlugin)
+            .add(ScorePlugin)
+            .add(GhostPlugin)
+            .add(TunnelPlugin)
+            .add(RandomPlugin)
+            .add(DifficultyPlugin)
+            .add(DirectorPlugin)
+            .add(ClockPlugin)
+            .add(NetworkPlugin)
+            .add(LivesPlugin)
+            .add(LevelPlugin)
+            .add(SpeedPlugin)
+            .add(InteractionsPlugin)
+            .add(GhostHouseGatePlugin)
+            .add(SpriteSheetPlugin)
+            .add(ReadyScreenPlugin)
+            .add(GameOverScreenPlugin)
+            .add(HighScoresPlugin)
+            .add(SpecsPerLevelPlugin);
+    }
+}
+
+/// An app that runs the whole game without window, rendering and sound, for tests. Every update advances the game by
+/// one tick of the given rate.
+#[cfg(test)]
+pub fn headless_app(ticks_per_second: u32) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(bevy::asset::AssetPlugin)
+        .add_plugin(bevy::render::texture::ImagePlugin)
+        .add_asset::<AudioSource>()
+        .init_asset_loader::<bevy::audio::AudioLoader>()
+        .init_resource::<Audio>()
+        .add_asset::<Font>()
+        .init_asset_loader::<bevy::text::FontLoader>()
+        .insert_resource(Input::<KeyCode>::default())
+        .insert_resource(crate::clock::FixedTick::per_second(ticks_per_second))
+        .add_plugins(GamePlugins);
+    app
+}
@@ -49,6 +49,10 @@ impl LoadedAssets {
         self.path_handle_map.get(&key.to_string()).expect("the requested handle should be registered").clone().typed()
     }
 
+    pub fn contains(&self, key: impl ToString) -> bool {
+        self.path_handle_map.contains_key(&key.to_string())
+    }
+
     pub fn get_asset<'a, T: Asset>(&'a self, key: impl ToString, assets: &'a Assets<T>) -> &T {
         let handle = self.get_handle::<_, T>(key);
         assets.get(&handle).unwrap()
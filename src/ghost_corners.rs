@@ -1,12 +1,9 @@
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use crate::common::position::Position;
-use crate::ghosts::Ghost;
-use crate::ghosts::Ghost::*;
-use crate::is;
+use crate::ghosts::GhostId;
 use crate::life_cycle::LifeCycle::Start;
-use crate::map::{Element, Map};
-use crate::map::Element::{BlinkyCorner, ClydeCorner, InkyCorner, PinkyCorner};
+use crate::map::Map;
 
 pub struct GhostCornersPlugin;
 
@@ -21,26 +18,17 @@ impl Plugin for GhostCornersPlugin {
 }
 
 pub struct GhostCorners {
-    corners: HashMap<Ghost, Position>,
+    corners: HashMap<GhostId, Position>,
 }
 
 impl GhostCorners {
-    fn new(map: &Map) -> Self {
+    pub(crate) fn new(map: &Map) -> Self {
         GhostCorners {
-            corners: [
-                (Blinky, Self::get_corner_position(map, is!(BlinkyCorner))),
-                (Pinky, Self::get_corner_position(map, is!(PinkyCorner))),
-                (Inky, Self::get_corner_position(map, is!(InkyCorner))),
-                (Clyde, Self::get_corner_position(map, is!(ClydeCorner))),
-            ].into_iter().collect()
+            corners: map.ghosts().into_iter().map(|g| (g.id, g.corner)).collect()
         }
     }
 
-    fn get_corner_position(map: &Map, filter: impl Fn(&Element) -> bool) -> Position {
-        *map.get_positions_matching(filter).into_iter().next().expect("every ghost should have a corner")
-    }
-
-    pub fn get_corner(&self, ghost: &Ghost) -> Position {
+    pub fn get_corner(&self, ghost: &GhostId) -> Position {
         *self.corners.get(ghost).expect("every ghost should have a corner")
     }
 }
@@ -50,4 +38,4 @@ fn spawn_ghost_corners(
     map: Res<Map>,
 ) {
     commands.insert_resource(GhostCorners::new(&map));
-}
\ No newline at end of file
+}
@@ -1,15 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use bevy::prelude::*;
 use crate::common::position::Position;
 use crate::{is, map};
 use map::Element;
 use crate::board_dimensions::BoardDimensions;
-use crate::ghosts::Ghost;
-use crate::ghosts::Ghost::*;
+use crate::ghosts::GhostId;
 use crate::common::Direction;
-use crate::life_cycle::LifeCycle::Start;
-use crate::map::{Map, Rotation, WallType};
+use crate::ghosts::state::{EGhostStateChanged, State, StateSetter};
+use crate::ghosts::target::LTargetSetter;
+use crate::life_cycle::LifeCycle::{Running, Start};
+use crate::map::{GhostDeclaration, Map, ReturnHouse, Rotation, SpawnSlot, WallType};
 use crate::map::Rotation::*;
+use crate::map::SpawnSlot::*;
 
 pub struct GhostHousePlugin;
 
@@ -17,40 +19,192 @@ impl Plugin for GhostHousePlugin {
     fn build(&self, app: &mut App) {
         app
             .add_system_set(
-                SystemSet::on_enter(Start).with_system(create_ghost_house)
+                SystemSet::on_enter(Start).with_system(create_ghost_houses)
+            )
+            .add_system_set(
+                SystemSet::on_update(Running).with_system(choose_house_of_eaten_ghosts.after(StateSetter).before(LTargetSetter))
             )
         ;
     }
 }
 
-fn create_ghost_house(
+fn create_ghost_houses(
     mut commands: Commands,
     map: Res<Map>,
     dimensions: Res<BoardDimensions>
 ) {
-    commands.insert_resource(GhostHouse::new(&map, &dimensions));
+    commands.insert_resource(GhostHouses::new(&map, &dimensions));
 }
 
-/// Resource that describes the ghost house, the place where ghosts start and respawn.
+/// An eaten ghost returns to the house the map declared for him, or to the house nearest to him (see ReturnHouse).
+fn choose_house_of_eaten_ghosts(
+    ghost_houses: Res<GhostHouses>,
+    mut event_reader: EventReader<EGhostStateChanged>,
+    mut query: Query<(&GhostId, &Transform, &mut CurrentHouse)>,
+) {
+    for event in event_reader.iter().filter(|e| e.to == State::Eaten && e.from != State::Eaten) {
+        if let Ok((id, transform, mut current_house)) = query.get_mut(event.entity) {
+            *current_house = ghost_houses.house_to_return_to(id, transform.translation);
+        }
+    }
+}
+
+/// The index of the house a ghost respawns in and leaves from (see GhostHouses).
+///
+/// Every ghost starts in his own house. It only changes if he returns to the nearest house when eaten.
+#[derive(Component, Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CurrentHouse(pub usize);
+
+/// Resource that holds every ghost house on the map.
 ///
-/// The ghost house is a fixed structure on the map bounded to a set of game rules.
+/// Every group of connected ghost walls and entrances is one house. The houses are numbered by their
+/// bottom left corner, from bottom to top and then from left to right. Every ghost belongs to the house
+/// the map declared for him (the first one by default). He spawns there and, unless the map lets him
+/// return to the nearest house, returns there when eaten.
+pub struct GhostHouses {
+    houses: Vec<GhostHouse>,
+    return_to: HashMap<GhostId, ReturnHouse>,
+}
+
+impl GhostHouses {
+    pub fn new(map: &Map, dimensions: &BoardDimensions) -> Self {
+        let ghosts = map.ghosts();
+        let houses = Self::find_houses(map)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (bottom_left, rotation))| {
+                let ghosts_in_house = ghosts.iter().filter(|g| g.house == i);
+                GhostHouse::new(bottom_left, rotation, ghosts_in_house, dimensions)
+            })
+            .collect::<Vec<_>>();
+
+        assert!(
+            ghosts.iter().all(|g| g.house < houses.len()),
+            "every ghost should belong to a ghost house on the map"
+        );
+
+        let return_to = ghosts.iter().map(|g| (g.id, g.return_to)).collect();
+        GhostHouses { houses, return_to }
+    }
+
+    /// Return the bottom left corner and the rotation of every house, in the order they are numbered.
+    fn find_houses(map: &Map) -> Vec<(Position, Rotation)> {
+        let walls = map
+            .get_positions_matching(is!(Element::Wall {wall_type: WallType::Ghost, ..}))
+            .into_iter()
+            .copied()
+            .collect::<HashSet<_>>();
+        let entrances = map
+            .position_element_iter()
+            .into_iter()
+            .filter_map(|(pos, elem)| match elem {
+                Element::GhostHouseEntrance {rotation} => Some((*pos, *rotation)),
+                _ => None
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut visited = HashSet::new();
+        let mut houses = vec![];
+
+        for start in &walls {
+            if visited.contains(start) {
+                continue;
+            }
+
+            let mut house = vec![];
+            let mut stack = vec![*start];
+            visited.insert(*start);
+
+            while let Some(pos) = stack.pop() {
+                house.push(pos);
+
+                for neighbour in pos.get_neighbours() {
+                    let next = neighbour.position;
+                    let part_of_house = walls.contains(&next) || entrances.contains_key(&next);
+
+                    if part_of_house && visited.insert(next) {
+                        stack.push(next)
+                    }
+                }
+            }
+
+            let bottom_left = house
+                .iter()
+                .filter(|pos| walls.contains(pos))
+                .fold(
+                    Position::new(isize::MAX, isize::MAX),
+                    |acc, pos| Position::new(isize::min(acc.x, pos.x), isize::min(acc.y, pos.y)),
+                );
+            let rotation = *house
+                .iter()
+                .find_map(|pos| entrances.get(pos))
+                .expect("every ghost house should have an entrance");
+            houses.push((bottom_left, rotation));
+        }
+
+        houses.sort_by_key(|(bottom_left, _)| (bottom_left.y, bottom_left.x));
+        houses
+    }
+
+    /// Return the house the given ghost belongs to.
+    pub fn house_of(&self, ghost: &GhostId) -> &GhostHouse {
+        self.get(&self.own_house_of(ghost))
+    }
+
+    pub fn get(&self, house: &CurrentHouse) -> &GhostHouse {
+        &self.houses[house.0]
+    }
+
+    fn own_house_of(&self, ghost: &GhostId) -> CurrentHouse {
+        let index = self.houses
+            .iter()
+            .position(|house| house.ghost_slots.contains_key(ghost))
+            .expect("every ghost should belong to a ghost house");
+        CurrentHouse(index)
+    }
+
+    /// Return the house the given ghost, eaten at the given coordinates, returns to.
+    pub fn house_to_return_to(&self, ghost: &GhostId, coordinates: Vec3) -> CurrentHouse {
+        match self.return_to.get(ghost) {
+            Some(ReturnHouse::Nearest) => self.nearest_house_to(coordinates),
+            _ => self.own_house_of(ghost),
+        }
+    }
+
+    /// The distance is measured in a straight line to the fields in front of the entrance.
+    fn nearest_house_to(&self, coordinates: Vec3) -> CurrentHouse {
+        let distance_to = |house: &GhostHouse| coordinates.truncate().distance(house.coordinates_in_front_of_entrance().truncate());
+        let index = self.houses
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| distance_to(a).total_cmp(&distance_to(b)))
+            .map(|(index, _)| index)
+            .expect("every map should have a ghost house");
+        CurrentHouse(index)
+    }
+}
+
+/// Describes a ghost house, the place where ghosts start and respawn.
+///
+/// A ghost house is a fixed structure on the map bounded to a set of game rules.
 /// It is a 6 x 3 field surrounded by walls, with a 2 x 1 opening centered on the top.
-/// Inky, Pinky and Clyde spawn (in this order) inside the house, Blinky spawns in front of
-/// the opening. Every ghost respawns at his start position, except Blinky who shares
-/// this spot with Pinky.
+/// It has four spawn slots: left, center and right inside the house, and one in front of
+/// the opening. Every ghost of the house spawns on the slot the map assigned to him and respawns there,
+/// except ghosts from the slot in front of the house, who respawn in the center. Ghosts from other
+/// houses respawn in the center as well.
 ///
 /// It should look like this:
-///    BB
+///    FF
 /// WWWEEWWW
 /// WHHHHHHW
-/// WIIPPCCW
+/// WLLCCRRW
 /// WHHHHHHW
 /// WWWWWWWW
 ///
-/// B = BlinkySpawn
-/// I = InkySpawn
-/// P = PinkySpawn
-/// C = ClydeSpawn
+/// F = InFrontOfEntrance (Blinky in the arcade)
+/// L = Left (Inky in the arcade)
+/// C = Center (Pinky in the arcade)
+/// R = Right (Clyde in the arcade)
 /// E = Entrance
 /// H = House
 /// W = Wall
@@ -59,55 +213,39 @@ fn create_ghost_house(
 //  future to rotate it. Therefore, everyone accessing the house acts relative to the house (like respecting the entrance direction).
 pub struct GhostHouse {
     pub entrance_direction: Direction,
-    spawns: HashMap<Ghost, Spawn>,
+    spawns: HashMap<SpawnSlot, Spawn>,
+    ghost_slots: HashMap<GhostId, SpawnSlot>,
 }
 
 impl GhostHouse {
-    pub fn new(map: &Map, dimensions: &BoardDimensions) -> Self {
-        let bottom_left = Self::get_bottom_left(map);
-        let rotation = Self::get_rotation(map);
+    fn new<'a>(
+        bottom_left: Position,
+        rotation: Rotation,
+        ghosts: impl IntoIterator<Item=&'a GhostDeclaration>,
+        dimensions: &BoardDimensions
+    ) -> Self {
         let spawns = Self::create_spawns(rotation, bottom_left, dimensions);
+        let ghost_slots = ghosts.into_iter().map(|g| (g.id, g.spawn_slot)).collect();
 
         GhostHouse {
             entrance_direction: Direction::Up.rotate(rotation),
             spawns,
+            ghost_slots,
         }
     }
 
-    fn get_bottom_left(map: &Map) -> Position {
-        map
-            .get_positions_matching(is!(Element::Wall {wall_type: WallType::Ghost, ..}))
-            .into_iter()
-            .fold(
-                Position::new(isize::MAX, isize::MAX),
-                |acc, pos| Position::new(isize::min(acc.x, pos.x), isize::min(acc.y, pos.y)),
-            )
-    }
-
-    fn get_rotation(map: &Map) -> Rotation {
-        map
-            .position_element_iter()
-            .into_iter()
-            .filter_map(|(_, elem)| match elem {
-                Element::GhostHouseEntrance {rotation} => Some(*rotation),
-                _ => None
-            })
-            .next()
-            .expect("the map should at least contain one ghost house entrance")
-    }
-
-    fn create_spawns(rotation: Rotation, bottom_left: Position, dimensions: &BoardDimensions) -> HashMap<Ghost, Spawn> {
+    fn create_spawns(rotation: Rotation, bottom_left: Position, dimensions: &BoardDimensions) -> HashMap<SpawnSlot, Spawn> {
         [
-            (Blinky, Self::create_blinky_spawn(rotation, bottom_left, dimensions)),
-            (Pinky, Self::create_pinky_spawn(rotation, bottom_left, dimensions)),
-            (Inky, Self::create_inky_spawn(rotation, bottom_left, dimensions)),
-            (Clyde, Self::create_clyde_spawn(rotation, bottom_left, dimensions)),
+            (InFrontOfEntrance, Self::create_in_front_of_entrance_spawn(rotation, bottom_left, dimensions)),
+            (Center, Self::create_center_spawn(rotation, bottom_left, dimensions)),
+            (Left, Self::create_left_spawn(rotation, bottom_left, dimensions)),
+            (Right, Self::create_right_spawn(rotation, bottom_left, dimensions)),
         ]
             .into_iter()
             .collect()
     }
 
-    fn create_blinky_spawn(rotation: Rotation, bottom_left: Position, dimensions: &BoardDimensions) -> Spawn {
+    fn create_in_front_of_entrance_spawn(rotation: Rotation, bottom_left: Position, dimensions: &BoardDimensions) -> Spawn {
         match rotation {
             D0 => Self::create_spawn_with_offsets(bottom_left, (3, 5), (4, 5), dimensions),
             D90 => Self::create_spawn_with_offsets(bottom_left, (5, 3), (5, 4), dimensions),
@@ -116,7 +254,7 @@ impl GhostHouse {
         }
     }
 
-    fn create_pinky_spawn(rotation: Rotation, bottom_left: Position, dimensions: &BoardDimensions) -> Spawn {
+    fn create_center_spawn(rotation: Rotation, bottom_left: Position, dimensions: &BoardDimensions) -> Spawn {
         match rotation {
             D0 => Self::create_spawn_with_offsets(bottom_left, (3, 2), (4, 2), dimensions),
             D90 => Self::create_spawn_with_offsets(bottom_left, (2, 3), (2, 4), dimensions),
@@ -125,7 +263,7 @@ impl GhostHouse {
         }
     }
 
-    fn create_inky_spawn(rotation: Rotation, bottom_left: Position, dimensions: &BoardDimensions) -> Spawn {
+    fn create_left_spawn(rotation: Rotation, bottom_left: Position, dimensions: &BoardDimensions) -> Spawn {
         match rotation {
             D0 => Self::create_spawn_with_offsets(bottom_left, (1, 2), (2, 2), dimensions),
             D90 => Self::create_spawn_with_offsets(bottom_left, (2, 5), (2, 6), dimensions),
@@ -134,7 +272,7 @@ impl GhostHouse {
         }
     }
 
-    fn create_clyde_spawn(rotation: Rotation, bottom_left: Position, dimensions: &BoardDimensions) -> Spawn {
+    fn create_right_spawn(rotation: Rotation, bottom_left: Position, dimensions: &BoardDimensions) -> Spawn {
         match rotation {
             D0 => Self::create_spawn_with_offsets(bottom_left, (5, 2), (6, 2), dimensions),
             D90 => Self::create_spawn_with_offsets(bottom_left, (2, 1), (2, 2), dimensions),
@@ -159,48 +297,133 @@ impl GhostHouse {
         Spawn { positions, coordinates }
     }
 
-    pub fn spawn_coordinates_of(&self, ghost: &Ghost) -> Vec3 {
+    pub fn spawn_coordinates_of(&self, ghost: &GhostId) -> Vec3 {
         self.spawn_of(ghost).coordinates
     }
 
-    /// Blinky respawns on Pinkys spawn coordinates
-    pub fn respawn_coordinates_of(&self, ghost: &Ghost) -> Vec3 {
-        if ghost == &Blinky {
-            self.spawn_coordinates_of(&Pinky)
-        } else {
-            self.spawn_coordinates_of(ghost)
+    /// Ghosts spawning in front of the house respawn in its center.
+    pub fn respawn_coordinates_of(&self, ghost: &GhostId) -> Vec3 {
+        match self.slot_of(ghost) {
+            InFrontOfEntrance => self.center_coordinates(),
+            _ => self.spawn_coordinates_of(ghost)
         }
     }
 
-    pub fn spawn_direction_of(&self, ghost: &Ghost) -> Direction {
-        match *ghost {
-            Blinky => self.entrance_direction.rotate_left(),
-            Pinky => self.entrance_direction,
+    pub fn spawn_direction_of(&self, ghost: &GhostId) -> Direction {
+        match self.slot_of(ghost) {
+            InFrontOfEntrance => self.entrance_direction.rotate_left(),
+            Center => self.entrance_direction,
             _ => self.entrance_direction.opposite()
         }
     }
 
-    /// Blinky always spawns in front of the ghost house.
     pub fn positions_in_front_of_entrance(&self) -> impl IntoIterator<Item=&Position> {
-        self.spawn_of(&Blinky).positions.iter()
+        self.spawn_at(&InFrontOfEntrance).positions.iter()
     }
 
-    /// Blinky always spawns in front of the ghost house.
     pub fn coordinates_in_front_of_entrance(&self) -> Vec3 {
-        self.spawn_of(&Blinky).coordinates
+        self.spawn_at(&InFrontOfEntrance).coordinates
     }
 
     /// Return the coordinates of the ghost house center. Every ghost moves to the center when leaving the house or entering for respawn.
     pub fn center_coordinates(&self) -> Vec3 {
-        self.spawn_of(&Pinky).coordinates
+        self.spawn_at(&Center).coordinates
+    }
+
+    /// Ghosts from other houses have no slot here and use the center.
+    fn slot_of(&self, ghost: &GhostId) -> SpawnSlot {
+        self.ghost_slots.get(ghost).copied().unwrap_or(Center)
     }
 
-    fn spawn_of(&self, ghost: &Ghost) -> &Spawn {
-        self.spawns.get(ghost).expect("every ghost should have a registered spawn")
+    fn spawn_of(&self, ghost: &GhostId) -> &Spawn {
+        self.spawn_at(&self.slot_of(ghost))
+    }
+
+    fn spawn_at(&self, slot: &SpawnSlot) -> &Spawn {
+        self.spawns.get(slot).expect("every slot should have a spawn")
     }
 }
 
 struct Spawn {
     pub coordinates: Vec3,
     pub positions: [Position; 2],
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board_dimensions::BoardDimensions;
+    use crate::common::position::Position;
+    use crate::ghost_house::{CurrentHouse, GhostHouses};
+    use crate::ghosts::Ghost::*;
+    use crate::ghosts::GhostId;
+    use crate::map::board::Board;
+    use crate::map::Element::*;
+    use crate::map::SpawnSlot::*;
+    use crate::map::{Field, Map, ReturnHouse, WallType};
+
+    /// The default map with a copy of its ghost house ten fields below, and a ghost in each house.
+    fn map_with_two_houses() -> Map {
+        let mut fields = serde_json::from_str::<Vec<Field>>(include_str!("../assets/maps/default.map.json")).unwrap();
+        let copied_house = fields
+            .iter()
+            .filter(|f| matches!(f.element, Wall { wall_type: WallType::Ghost, .. } | GhostHouseEntrance { .. }))
+            .map(|f| Field {
+                position: Position::new(f.position.x, f.position.y - 10),
+                element: f.element.clone(),
+            })
+            .collect::<Vec<_>>();
+        fields.extend(copied_house);
+        fields.push(Field {
+            position: Position::new(0, 30),
+            element: GhostCorner { ghost: Blinky, spawn_slot: Center, release_order: 0, house: 1, brain: None, return_to: ReturnHouse::Own },
+        });
+        fields.push(Field {
+            position: Position::new(31, 0),
+            element: GhostCorner { ghost: Clyde, spawn_slot: Center, release_order: 1, house: 0, brain: None, return_to: ReturnHouse::Nearest },
+        });
+        Map::from_fields(fields)
+    }
+
+    fn dimensions(map: &Map) -> BoardDimensions {
+        BoardDimensions::new(&Board::new(map))
+    }
+
+    #[test]
+    fn the_default_map_has_one_house_for_all_ghosts() {
+        let map = Map::load_default();
+        let houses = GhostHouses::new(&map, &dimensions(&map));
+
+        assert_eq!(houses.houses.len(), 1);
+        assert!((0..4).all(|id| houses.house_of(&GhostId(id)).center_coordinates() == houses.houses[0].center_coordinates()));
+    }
+
+    #[test]
+    fn every_ghost_spawns_and_respawns_in_his_own_house() {
+        let map = map_with_two_houses();
+        let dimensions = dimensions(&map);
+        let houses = GhostHouses::new(&map, &dimensions);
+
+        assert_eq!(houses.houses.len(), 2);
+
+        let upper_house = houses.house_of(&GhostId(0));
+        let lower_house = houses.house_of(&GhostId(1));
+        assert!(upper_house.center_coordinates().y > lower_house.center_coordinates().y);
+        assert_eq!(upper_house.spawn_coordinates_of(&GhostId(0)), upper_house.center_coordinates());
+        assert_eq!(lower_house.respawn_coordinates_of(&GhostId(1)), lower_house.center_coordinates());
+    }
+
+    #[test]
+    fn an_eaten_ghost_returns_to_his_own_house_or_the_nearest_one() {
+        let map = map_with_two_houses();
+        let houses = GhostHouses::new(&map, &dimensions(&map));
+        let in_front_of_lower_house = houses.houses[0].coordinates_in_front_of_entrance();
+        let in_front_of_upper_house = houses.houses[1].coordinates_in_front_of_entrance();
+
+        assert_eq!(houses.house_to_return_to(&GhostId(0), in_front_of_lower_house), CurrentHouse(1));
+        assert_eq!(houses.house_to_return_to(&GhostId(1), in_front_of_lower_house), CurrentHouse(0));
+        assert_eq!(houses.house_to_return_to(&GhostId(1), in_front_of_upper_house), CurrentHouse(1));
+
+        let upper_house = houses.get(&CurrentHouse(1));
+        assert_eq!(upper_house.respawn_coordinates_of(&GhostId(1)), upper_house.center_coordinates());
+    }
+}
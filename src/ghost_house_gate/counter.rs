@@ -1,45 +1,48 @@
-use crate::level::Level;
+use std::fmt::{Display, Formatter};
+
+use crate::specs_per_level::Spec;
 use ActiveCounter::*;
-use std::collections::HashMap;
-use crate::ghosts::Ghost;
-use crate::ghosts::Ghost::*;
 
+/// Counts the eaten dots for the ghosts of one house. Ghosts are referred to by their index in release order.
+#[derive(Debug)]
 pub (in crate::ghost_house_gate) struct Counter {
     active_counter: ActiveCounter,
     per_ghost_counter: PerGhostCounter,
-    global_counter: Option<GlobalCounter>
+    global_counter: Option<GlobalCounter>,
+    global_limits: Vec<usize>,
 }
 
 impl Counter {
-    pub fn new(level: &Level) -> Self {
+    pub fn new(spec: &Spec, num_ghosts: usize) -> Self {
         Counter {
             active_counter: PerGhost,
-            per_ghost_counter: PerGhostCounter::new_for_level(level),
-            global_counter: None
+            per_ghost_counter: PerGhostCounter::new(&spec.ghost_house_dot_limits, num_ghosts),
+            global_counter: None,
+            global_limits: create_limits(&spec.ghost_house_global_dot_limits, num_ghosts),
         }
     }
 
-    pub fn increment(&mut self, current_ghost: &Ghost) {
+    pub fn increment(&mut self, ghost_index: usize) {
         match self.active_counter {
-            PerGhost => self.per_ghost_counter.increment(current_ghost),
+            PerGhost => self.per_ghost_counter.increment(ghost_index),
             Global => self.global_counter.as_mut().unwrap().increment()
         }
     }
 
     pub fn switch_to_global(&mut self) {
         self.active_counter = Global;
-        self.global_counter = Some(GlobalCounter::new())
+        self.global_counter = Some(GlobalCounter::new(self.global_limits.clone()))
     }
 
     /// Check if the limit for the current ghost is reached.
     ///
     /// Also switches from the global counter to the per ghost counter if the global counter is finished.
-    pub fn limit_reached(&mut self, current_ghost: &Ghost) -> bool {
+    pub fn limit_reached(&mut self, ghost_index: usize) -> bool {
         match self.active_counter {
-            PerGhost => self.per_ghost_counter.limit_reached_for_ghost(current_ghost),
+            PerGhost => self.per_ghost_counter.limit_reached_for_ghost(ghost_index),
             Global => {
                 let global_counter = self.global_counter.as_ref().unwrap();
-                let result = global_counter.limit_reached_for_ghost(current_ghost);
+                let result = global_counter.limit_reached_for_ghost(ghost_index);
                 if global_counter.is_finished() {
                     self.active_counter = PerGhost;
                 }
@@ -49,55 +52,48 @@ impl Counter {
     }
 }
 
+#[derive(Debug)]
 enum ActiveCounter {
     PerGhost,
     Global,
 }
 
+/// Counts the eaten dots of every ghost. The limits come from the spec of the level, in release order.
+#[derive(Debug)]
 struct PerGhostCounter {
-    ghost_counter_map: HashMap<Ghost, usize>,
-    ghost_limit_map: HashMap<Ghost, usize>,
+    ghost_counters: Vec<usize>,
+    ghost_limits: Vec<usize>,
 }
 
 impl PerGhostCounter {
-    fn new_for_level(level: &Level) -> Self {
-        let ghost_counter_map = create_ghost_value_map(0, 0, 0, 0);
-
-        match **level {
-            1 => PerGhostCounter {
-                ghost_counter_map,
-                ghost_limit_map: create_ghost_value_map(0, 0, 30, 60),
-            },
-            2 => PerGhostCounter {
-                ghost_counter_map,
-                ghost_limit_map: create_ghost_value_map(0, 0, 0, 50),
-            },
-            _ => PerGhostCounter {
-                ghost_counter_map,
-                ghost_limit_map: create_ghost_value_map(0, 0, 0, 0),
-            }
+    fn new(limits: &[usize], num_ghosts: usize) -> Self {
+        PerGhostCounter {
+            ghost_counters: vec![0; num_ghosts],
+            ghost_limits: create_limits(limits, num_ghosts),
         }
     }
 
-    fn increment(&mut self, current_ghost: &Ghost) {
-        *self.ghost_counter_map.get_mut(current_ghost).unwrap() += 1
+    fn increment(&mut self, ghost_index: usize) {
+        self.ghost_counters[ghost_index] += 1
     }
 
-    fn limit_reached_for_ghost(&self, current_ghost: &Ghost) -> bool {
-        self.ghost_counter_map.get(current_ghost).unwrap() == self.ghost_limit_map.get(current_ghost).unwrap()
+    fn limit_reached_for_ghost(&self, ghost_index: usize) -> bool {
+        self.ghost_counters[ghost_index] == self.ghost_limits[ghost_index]
     }
 }
 
+/// A counter for all ghosts. It is finished when the limit of the last ghost in release order is reached.
+#[derive(Debug)]
 struct GlobalCounter {
     value: usize,
-    ghost_limit_map: HashMap<Ghost, usize>,
+    ghost_limits: Vec<usize>,
 }
 
 impl GlobalCounter {
-    fn new() -> Self {
+    fn new(ghost_limits: Vec<usize>) -> Self {
         GlobalCounter {
             value: 0,
-            ghost_limit_map: create_ghost_value_map(0, 7, 17, 32)
+            ghost_limits,
         }
     }
 
@@ -105,20 +101,141 @@ impl GlobalCounter {
         self.value += 1
     }
 
-    fn limit_reached_for_ghost(&self, current_ghost: &Ghost) -> bool {
-        *self.ghost_limit_map.get(current_ghost).unwrap() == self.value
+    fn limit_reached_for_ghost(&self, ghost_index: usize) -> bool {
+        self.ghost_limits[ghost_index] == self.value
     }
 
     fn is_finished(&self) -> bool {
-        *self.ghost_limit_map.get(&Clyde).unwrap() == self.value
+        self.ghost_limits.last().is_none_or(|limit| *limit == self.value)
     }
 }
 
-fn create_ghost_value_map(blinky_val: usize, pinky_val: usize, inky_val: usize, clyde_val: usize) -> HashMap<Ghost, usize> {
-    let mut map = HashMap::with_capacity(4);
-    map.insert(Blinky, blinky_val);
-    map.insert(Pinky, pinky_val);
-    map.insert(Inky, inky_val);
-    map.insert(Clyde, clyde_val);
-    map
-}
\ No newline at end of file
+/// Take the given limits in release order and repeat the last one for every further ghost. Without limits,
+/// no ghost waits for dots.
+fn create_limits(limits: &[usize], num_ghosts: usize) -> Vec<usize> {
+    (0..num_ghosts)
+        .map(|i| *limits.get(i).or(limits.last()).unwrap_or(&0))
+        .collect()
+}
+
+impl Display for Counter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.active_counter, &self.global_counter) {
+            (Global, Some(global_counter)) => write!(
+                f,
+                "global counter: {} | limits: {}",
+                global_counter.value,
+                join(&global_counter.ghost_limits)
+            ),
+            _ => write!(
+                f,
+                "per ghost counter: {} | limits: {}",
+                join(&self.per_ghost_counter.ghost_counters),
+                join(&self.per_ghost_counter.ghost_limits)
+            ),
+        }
+    }
+}
+
+fn join(values: &[usize]) -> String {
+    values.iter().map(usize::to_string).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ghost_house_gate::counter::{create_limits, Counter};
+    use crate::level::Level;
+    use crate::specs_per_level::{Spec, SpecsPerLevel};
+
+    fn counter(level: usize, num_ghosts: usize) -> Counter {
+        Counter::new(SpecsPerLevel::arcade().get_for(&Level(level)), num_ghosts)
+    }
+
+    fn eat(counter: &mut Counter, ghost_index: usize, dots: usize) {
+        for _ in 0..dots {
+            counter.increment(ghost_index)
+        }
+    }
+
+    #[test]
+    fn the_per_ghost_limits_depend_on_the_level() {
+        let level_1 = counter(1, 4);
+        let level_2 = counter(2, 4);
+        let level_3 = counter(3, 4);
+
+        assert_eq!(level_1.per_ghost_counter.ghost_limits, vec![0, 0, 30, 60]);
+        assert_eq!(level_2.per_ghost_counter.ghost_limits, vec![0, 0, 0, 50]);
+        assert_eq!(level_3.per_ghost_counter.ghost_limits, vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn further_ghosts_get_the_limit_of_the_last_arcade_ghost() {
+        let counter = counter(1, 6);
+
+        assert_eq!(counter.per_ghost_counter.ghost_limits, vec![0, 0, 30, 60, 60, 60]);
+    }
+
+    #[test]
+    fn only_the_counter_of_the_given_ghost_is_incremented() {
+        let mut counter = counter(1, 4);
+
+        eat(&mut counter, 2, 29);
+        assert!(!counter.limit_reached(2));
+        assert_eq!(counter.per_ghost_counter.ghost_counters, vec![0, 0, 29, 0]);
+
+        eat(&mut counter, 2, 1);
+        assert!(counter.limit_reached(2));
+        assert!(!counter.limit_reached(3));
+    }
+
+    #[test]
+    fn the_global_counter_releases_at_7_17_and_32_dots() {
+        let mut counter = counter(1, 4);
+        counter.switch_to_global();
+
+        assert!(counter.limit_reached(0));
+        eat(&mut counter, 1, 7);
+        assert!(counter.limit_reached(1));
+        eat(&mut counter, 2, 10);
+        assert!(counter.limit_reached(2));
+        eat(&mut counter, 3, 14);
+        assert!(!counter.limit_reached(3));
+        eat(&mut counter, 3, 1);
+        assert!(counter.limit_reached(3));
+    }
+
+    #[test]
+    fn the_per_ghost_counter_takes_over_with_its_old_values_when_the_global_counter_is_finished() {
+        let mut counter = counter(1, 4);
+        eat(&mut counter, 2, 20);
+        counter.switch_to_global();
+
+        eat(&mut counter, 3, 32);
+        assert!(counter.limit_reached(3));
+
+        eat(&mut counter, 2, 10);
+        assert!(counter.limit_reached(2));
+        assert_eq!(counter.per_ghost_counter.ghost_counters, vec![0, 0, 30, 0]);
+    }
+
+    #[test]
+    fn the_global_limits_come_from_the_spec() {
+        let spec = Spec {
+            ghost_house_global_dot_limits: vec![0, 2, 4, 6],
+            ..Spec::default()
+        };
+        let mut counter = Counter::new(&spec, 5);
+        counter.switch_to_global();
+
+        eat(&mut counter, 1, 2);
+        assert!(counter.limit_reached(1));
+        assert_eq!(counter.global_counter.as_ref().unwrap().ghost_limits, vec![0, 2, 4, 6, 6]);
+    }
+
+    #[test]
+    fn limits_of_any_length_are_repeated_or_cut_to_the_number_of_ghosts() {
+        assert_eq!(create_limits(&[0, 5, 10, 15, 20], 4), vec![0, 5, 10, 15]);
+        assert_eq!(create_limits(&[0, 5], 4), vec![0, 5, 5, 5]);
+        assert_eq!(create_limits(&[], 3), vec![0, 0, 0]);
+    }
+}
@@ -1,18 +1,19 @@
 use bevy::prelude::*;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
 use std::time::Duration;
 
 use crate::ghost_house_gate::counter::Counter;
-use crate::ghosts::Ghost;
-use crate::ghosts::Ghost::*;
-use crate::interactions::{ECapmanHit, EDotEaten};
+use crate::ghosts::GhostId;
+use crate::ghosts::state::StateSetter;
+use crate::interactions::{EDotEaten, LCapmanInteractions};
 use crate::level::Level;
 use crate::life_cycle::LifeCycle::*;
+use crate::map::Map;
+use crate::specs_per_level::{Spec, SpecsPerLevel};
 
 mod counter;
 
-const NUM_GHOST_TYPES: usize = 4;
-
 pub struct GhostHouseGatePlugin;
 
 impl Plugin for GhostHouseGatePlugin {
@@ -20,40 +21,120 @@ impl Plugin for GhostHouseGatePlugin {
         app.add_system_set(SystemSet::on_enter(Start).with_system(create_gate))
             .add_system_set(
                 SystemSet::on_update(Running)
-                    .with_system(update_ghost_house_gate)
-                    .with_system(increment_counter_when_dot_eaten)
+                    .with_system(recreate_gates_when_level_changed)
+                    .with_system(update_ghost_house_gate.after(recreate_gates_when_level_changed))
+                    .with_system(increment_counter_when_dot_eaten.after(update_ghost_house_gate))
+                    .after(LCapmanInteractions)
+                    .before(StateSetter),
+            )
+            .add_system_set(
+                SystemSet::on_enter(CapmanHit)
                     .with_system(switch_to_global_counter_when_capman_got_killed),
             );
     }
 }
 
-fn create_gate(mut commands: Commands, level: Res<Level>) {
-    commands.insert_resource(GhostHouseGate::new(&level));
+fn create_gate(
+    mut commands: Commands,
+    map: Res<Map>,
+    level: Res<Level>,
+    specs_per_level: Res<SpecsPerLevel>,
+) {
+    commands.insert_resource(GhostHouseGates::new(&map, specs_per_level.get_for(&level)));
+}
+
+/// Every level starts with fresh gates, using the release parameters of the new level.
+fn recreate_gates_when_level_changed(
+    mut ghost_house_gates: ResMut<GhostHouseGates>,
+    map: Res<Map>,
+    level: Res<Level>,
+    specs_per_level: Res<SpecsPerLevel>,
+) {
+    if !level.is_changed() {
+        return;
+    }
+
+    *ghost_house_gates = GhostHouseGates::new(&map, specs_per_level.get_for(&level));
 }
 
-fn update_ghost_house_gate(time: Res<Time>, mut ghost_house_gate: ResMut<GhostHouseGate>) {
-    ghost_house_gate.update(time.delta())
+fn update_ghost_house_gate(time: Res<Time>, mut ghost_house_gates: ResMut<GhostHouseGates>) {
+    ghost_house_gates.step(GateStep::TimePassed(time.delta()))
 }
 
+/// Every house counts the eaten dots on its own.
 fn increment_counter_when_dot_eaten(
     mut event_reader: EventReader<EDotEaten>,
-    mut ghost_house_gate: ResMut<GhostHouseGate>,
+    mut ghost_house_gates: ResMut<GhostHouseGates>,
 ) {
     for _ in event_reader.iter() {
-        ghost_house_gate.increment_counter()
+        ghost_house_gates.step(GateStep::DotEaten)
     }
 }
 
-fn switch_to_global_counter_when_capman_got_killed(
-    mut event_reader: EventReader<ECapmanHit>,
-    mut ghost_house_gate: ResMut<GhostHouseGate>,
-) {
-    for _ in event_reader.iter() {
-        ghost_house_gate.switch_to_global_counter()
+/// Only called when the game as a whole lost a life. If a capman dies while others keep
+/// playing, the ghosts stay where they are and the gate keeps its state.
+fn switch_to_global_counter_when_capman_got_killed(mut ghost_house_gates: ResMut<GhostHouseGates>) {
+    ghost_house_gates.step(GateStep::CapmanDied)
+}
+
+/// Resource that holds the gate of every ghost house (see GhostHouses).
+///
+/// The gates work independently from each other. Each one only releases the ghosts of its own house.
+#[derive(Debug)]
+pub struct GhostHouseGates {
+    gates: Vec<GhostHouseGate>,
+}
+
+impl GhostHouseGates {
+    pub(crate) fn new(map: &Map, spec: &Spec) -> Self {
+        let mut ghosts_per_house = BTreeMap::<usize, Vec<GhostId>>::new();
+
+        for ghost in map.ghosts() {
+            ghosts_per_house.entry(ghost.house).or_default().push(ghost.id)
+        }
+
+        let gates = ghosts_per_house
+            .into_values()
+            .map(|ghosts| GhostHouseGate::new(ghosts, spec))
+            .collect();
+        GhostHouseGates { gates }
+    }
+
+    /// Return the gate of the house the given ghost belongs to.
+    pub fn gate_of(&self, ghost: &GhostId) -> &GhostHouseGate {
+        self.gates
+            .iter()
+            .find(|gate| gate.ghosts.contains(ghost))
+            .expect("every ghost should have a ghost house gate")
+    }
+
+    pub fn all_ghosts_released(&self) -> bool {
+        self.gates.iter().all(GhostHouseGate::all_ghosts_released)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &GhostHouseGate> {
+        self.gates.iter()
     }
+
+    fn step(&mut self, step: GateStep) {
+        for gate in &mut self.gates {
+            gate.step(step);
+        }
+    }
+}
+
+/// Something that happened in the game which the gate reacts to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GateStep {
+    DotEaten,
+    TimePassed(Duration),
+    /// The game as a whole lost a life. Every ghost is back in the house.
+    CapmanDied,
+    /// The ghost left the house without the gate releasing him.
+    GhostReleased(GhostId),
 }
 
-/// Resource that tells if ghost can leave the ghost house.
+/// Tells if a ghost can leave his ghost house.
 ///
 /// This is the most complex piece of logic in this entire game. It basically works like this:
 ///
@@ -64,122 +145,352 @@ fn switch_to_global_counter_when_capman_got_killed(
 /// If the per ghost counter is active, a ghost can leave if its personal limit is reached. Only
 /// the counter from the currently waiting ghost is incremented.
 ///
-/// The order of preference for ghosts is Blinky, Pinky, Inky and Clyde. Blinky and Pinky can always
-/// leave the house at the beginning of the game.
+/// The order of preference for ghosts is their release order on the map (in the arcade Blinky, Pinky, Inky
+/// and Clyde). The first two ghosts of a house can always leave it at the beginning of the game.
 ///
 /// If capman dies, the per ghost counter is switched with a newly initialized global one (while retaining
 /// the per ghost one). The waiting ghost can now leave when its predefined limit is reached. When
-/// Clyde left the house, the counter switches back to the per ghost one.
+/// the last ghost (Clyde in the arcade) left the house, the counter switches back to the per ghost one.
 ///
 /// There is also a timer active. If the timer reaches zero, the waiting ghost can return immediately.
 /// The timer gets reset when capman eats a dot.
+///
+/// The gate is a plain state machine without any bevy dependencies. It only changes through steps
+/// (see GateStep), which makes it possible to test it against the arcade rules.
+#[derive(Debug)]
 pub struct GhostHouseGate {
-    released_ghosts: HashSet<Ghost>,
-    ghost_preference_iterator: GhostPreferenceIterator,
-    current_waiting_ghost: Ghost,
+    /// The ghosts of the house, in order of preference.
+    ghosts: Vec<GhostId>,
+    released_ghosts: BTreeSet<GhostId>,
+    /// The index of the waiting ghost in the order of preference.
+    current_waiting_ghost: usize,
     counter: Counter,
-    release_timer: Timer,
+    release_time: Duration,
+    time_since_last_dot: Duration,
 }
 
 impl GhostHouseGate {
-    fn new(level: &Level) -> Self {
-        let mut iterator = GhostPreferenceIterator::new();
-        let current_waiting_ghost = iterator.next().unwrap();
+    fn new(ghosts: Vec<GhostId>, spec: &Spec) -> Self {
+        assert!(!ghosts.is_empty(), "a ghost house gate needs at least one ghost");
 
         GhostHouseGate {
-            released_ghosts: HashSet::with_capacity(NUM_GHOST_TYPES),
-            ghost_preference_iterator: iterator,
-            current_waiting_ghost,
-            counter: Counter::new(level),
-            release_timer: Self::create_release_timer_for_level(level),
+            released_ghosts: BTreeSet::new(),
+            current_waiting_ghost: 0,
+            counter: Counter::new(spec, ghosts.len()),
+            ghosts,
+            release_time: Duration::from_secs_f32(spec.ghost_house_release_time),
+            time_since_last_dot: Duration::ZERO,
         }
     }
 
-    fn create_release_timer_for_level(level: &Level) -> Timer {
-        match **level {
-            l if l < 5 => Timer::from_seconds(4.0, false),
-            _ => Timer::from_seconds(3.0, false),
-        }
+    /// Ask the gate if the given ghost can be released.
+    pub fn ghost_can_leave_house(&self, ghost: &GhostId) -> bool {
+        self.released_ghosts.contains(ghost)
     }
 
-    /// Ask the gate if the given ghost type can be released.
-    pub fn ghost_can_leave_house(&self, ghost: &Ghost) -> bool {
-        self.released_ghosts.contains(ghost)
+    pub fn all_ghosts_released(&self) -> bool {
+        self.released_ghosts.len() == self.ghosts.len()
     }
 
-    /// Increment the current counter. Typically when a dot was eaten.
-    /// Also resets the release timer.
-    fn increment_counter(&mut self) {
-        self.release_timer.reset();
-        self.counter.increment(&self.current_waiting_ghost)
+    /// Let the gate react to the given step. Returns the ghost that was released because of it, if any.
+    ///
+    /// At most one ghost gets released per step.
+    pub fn step(&mut self, step: GateStep) -> Option<GhostId> {
+        match step {
+            GateStep::DotEaten => self.dot_eaten(),
+            GateStep::TimePassed(delta) => self.time_passed(delta),
+            GateStep::CapmanDied => {
+                self.capman_died();
+                None
+            }
+            GateStep::GhostReleased(ghost) => self.ghost_released(ghost),
+        }
     }
 
-    /// Switch to the global counter. Typically called when capman died.
-    fn switch_to_global_counter(&mut self) {
-        self.counter.switch_to_global();
-        self.release_timer.reset();
-        self.released_ghosts.clear();
-        self.ghost_preference_iterator = GhostPreferenceIterator::new();
-        self.current_waiting_ghost = self
-            .ghost_preference_iterator
-            .next()
-            .expect("first item should exists");
+    /// Increment the current counter and reset the release timer.
+    fn dot_eaten(&mut self) -> Option<GhostId> {
+        self.time_since_last_dot = Duration::ZERO;
+
+        if self.all_ghosts_released() {
+            return None;
+        }
+
+        self.counter.increment(self.current_waiting_ghost);
+        self.release_if_limit_reached()
     }
 
     /// Proceed the release timer and check if the current waiting ghost can be released.
-    fn update(&mut self, delta: Duration) {
+    fn time_passed(&mut self, delta: Duration) -> Option<GhostId> {
         if self.all_ghosts_released() {
-            return;
+            return None;
+        }
+
+        self.time_since_last_dot += delta;
+
+        if self.time_since_last_dot >= self.release_time {
+            self.time_since_last_dot = Duration::ZERO;
+            Some(self.release_current_waiting_ghost())
+        } else {
+            self.release_if_limit_reached()
         }
+    }
 
-        self.release_timer.tick(delta);
+    /// Switch to the global counter. Every ghost is back in the house and waits again.
+    fn capman_died(&mut self) {
+        self.counter.switch_to_global();
+        self.time_since_last_dot = Duration::ZERO;
+        self.released_ghosts.clear();
+        self.current_waiting_ghost = 0;
+    }
 
-        if self.release_timer.finished() {
-            self.release_timer.reset();
-            self.release_current_waiting_ghost();
-        } else if self.counter.limit_reached(&self.current_waiting_ghost) {
-            self.release_current_waiting_ghost()
+    /// Mark the ghost as released, if he belongs to this house and still waits. If it was his turn, the next
+    /// ghost waits now.
+    fn ghost_released(&mut self, ghost: GhostId) -> Option<GhostId> {
+        if !self.ghosts.contains(&ghost) || !self.released_ghosts.insert(ghost) {
+            return None;
         }
+
+        self.skip_released_ghosts();
+        Some(ghost)
     }
 
-    fn all_ghosts_released(&self) -> bool {
-        self.released_ghosts.len() == NUM_GHOST_TYPES
+    fn release_if_limit_reached(&mut self) -> Option<GhostId> {
+        match self.counter.limit_reached(self.current_waiting_ghost) {
+            true => Some(self.release_current_waiting_ghost()),
+            false => None,
+        }
     }
 
-    fn release_current_waiting_ghost(&mut self) {
-        self.released_ghosts.insert(self.current_waiting_ghost);
+    fn release_current_waiting_ghost(&mut self) -> GhostId {
+        let released = self.ghosts[self.current_waiting_ghost];
+        self.released_ghosts.insert(released);
+        self.skip_released_ghosts();
+        released
+    }
 
-        if let Some(id) = self.ghost_preference_iterator.next() {
-            self.current_waiting_ghost = id
+    /// Move on to the next ghost that was not released yet. Once every ghost is released, the last one stays
+    /// the waiting ghost.
+    fn skip_released_ghosts(&mut self) {
+        while self.current_waiting_ghost + 1 < self.ghosts.len()
+            && self.released_ghosts.contains(&self.ghosts[self.current_waiting_ghost])
+        {
+            self.current_waiting_ghost += 1
         }
     }
 }
 
-struct GhostPreferenceIterator {
-    ghost_preferences: [Ghost; NUM_GHOST_TYPES],
-    current: usize,
-}
+impl Display for GhostHouseGate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let waiting = match self.all_ghosts_released() {
+            true => "-".to_string(),
+            false => self.ghosts[self.current_waiting_ghost].0.to_string(),
+        };
 
-impl GhostPreferenceIterator {
-    fn new() -> Self {
-        GhostPreferenceIterator {
-            ghost_preferences: [Blinky, Pinky, Inky, Clyde],
-            current: 0,
-        }
+        write!(
+            f,
+            "{} | waiting: {} | release timer: {:.1}/{:.1}",
+            self.counter,
+            waiting,
+            self.time_since_last_dot.as_secs_f32(),
+            self.release_time.as_secs_f32()
+        )
     }
 }
 
-impl Iterator for GhostPreferenceIterator {
-    type Item = Ghost;
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.current {
-            c if c >= NUM_GHOST_TYPES => None,
-            ref mut c => {
-                let next = self.ghost_preferences[*c];
-                *c += 1;
-                Some(next)
-            }
-        }
+    use crate::ghost_house_gate::{GateStep, GhostHouseGate};
+    use crate::ghosts::GhostId;
+    use crate::level::Level;
+    use crate::specs_per_level::{Spec, SpecsPerLevel};
+
+    fn spec(level: usize) -> Spec {
+        SpecsPerLevel::arcade().get_for(&Level(level)).clone()
+    }
+
+    fn gate(level: usize) -> GhostHouseGate {
+        GhostHouseGate::new((0..4).map(GhostId).collect(), &spec(level))
+    }
+
+    /// Let no time pass until the gate stops releasing ghosts.
+    fn release_waiting_ghosts(gate: &mut GhostHouseGate) -> Vec<GhostId> {
+        std::iter::from_fn(|| gate.step(GateStep::TimePassed(Duration::ZERO))).collect()
+    }
+
+    /// Eat the given amount of dots and return the ghosts released while doing so.
+    fn eat_dots(gate: &mut GhostHouseGate, dots: usize) -> Vec<GhostId> {
+        (0..dots)
+            .filter_map(|_| gate.step(GateStep::DotEaten))
+            .collect()
+    }
+
+    #[test]
+    fn the_first_two_ghosts_leave_immediately_on_level_1() {
+        let mut gate = gate(1);
+
+        assert_eq!(release_waiting_ghosts(&mut gate), vec![GhostId(0), GhostId(1)]);
+        assert!(gate.ghost_can_leave_house(&GhostId(1)));
+        assert!(!gate.ghost_can_leave_house(&GhostId(2)));
+    }
+
+    #[test]
+    fn on_level_1_the_third_ghost_waits_30_dots_and_the_fourth_60_more() {
+        let mut gate = gate(1);
+        release_waiting_ghosts(&mut gate);
+
+        assert_eq!(eat_dots(&mut gate, 29), vec![]);
+        assert_eq!(eat_dots(&mut gate, 1), vec![GhostId(2)]);
+        assert_eq!(eat_dots(&mut gate, 59), vec![]);
+        assert_eq!(eat_dots(&mut gate, 1), vec![GhostId(3)]);
+        assert!(gate.all_ghosts_released());
+    }
+
+    #[test]
+    fn on_level_2_only_the_fourth_ghost_waits_50_dots() {
+        let mut gate = gate(2);
+
+        assert_eq!(release_waiting_ghosts(&mut gate).len(), 3);
+        assert_eq!(eat_dots(&mut gate, 49), vec![]);
+        assert_eq!(eat_dots(&mut gate, 1), vec![GhostId(3)]);
+    }
+
+    #[test]
+    fn from_level_3_every_ghost_leaves_immediately() {
+        let mut gate = gate(3);
+
+        assert_eq!(release_waiting_ghosts(&mut gate).len(), 4);
+        assert!(gate.all_ghosts_released());
+    }
+
+    #[test]
+    fn the_release_timer_releases_the_waiting_ghost_after_4_seconds_without_dots() {
+        let mut gate = gate(1);
+        release_waiting_ghosts(&mut gate);
+
+        assert_eq!(gate.step(GateStep::TimePassed(Duration::from_secs_f32(3.9))), None);
+        assert_eq!(gate.step(GateStep::TimePassed(Duration::from_secs_f32(0.1))), Some(GhostId(2)));
+        assert_eq!(gate.step(GateStep::TimePassed(Duration::from_secs(4))), Some(GhostId(3)));
+    }
+
+    #[test]
+    fn eating_a_dot_resets_the_release_timer() {
+        let mut gate = gate(1);
+        release_waiting_ghosts(&mut gate);
+
+        gate.step(GateStep::TimePassed(Duration::from_secs(3)));
+        gate.step(GateStep::DotEaten);
+
+        assert_eq!(gate.step(GateStep::TimePassed(Duration::from_secs(3))), None);
+        assert_eq!(gate.step(GateStep::TimePassed(Duration::from_secs(1))), Some(GhostId(2)));
+    }
+
+    #[test]
+    fn from_level_5_the_release_timer_runs_3_seconds() {
+        let gate = GhostHouseGate::new(vec![GhostId(0)], &spec(5));
+
+        assert_eq!(gate.release_time, Duration::from_secs(3));
+    }
+
+    #[test]
+    fn the_release_parameters_come_from_the_spec() {
+        let spec = Spec {
+            ghost_house_dot_limits: vec![0, 5, 5, 5],
+            ghost_house_global_dot_limits: vec![0, 1, 2, 3],
+            ghost_house_release_time: 2.0,
+            ..Spec::default()
+        };
+        let mut gate = GhostHouseGate::new((0..3).map(GhostId).collect(), &spec);
+
+        assert_eq!(gate.release_time, Duration::from_secs(2));
+        assert_eq!(release_waiting_ghosts(&mut gate), vec![GhostId(0)]);
+        assert_eq!(eat_dots(&mut gate, 5), vec![GhostId(1)]);
+
+        gate.step(GateStep::CapmanDied);
+        release_waiting_ghosts(&mut gate);
+        assert_eq!(eat_dots(&mut gate, 1), vec![GhostId(1)]);
+        assert_eq!(eat_dots(&mut gate, 1), vec![GhostId(2)]);
+    }
+
+    #[test]
+    fn after_capman_died_every_ghost_waits_again_for_the_global_counter() {
+        let mut gate = gate(3);
+        release_waiting_ghosts(&mut gate);
+
+        gate.step(GateStep::CapmanDied);
+        assert!(!gate.ghost_can_leave_house(&GhostId(0)));
+
+        assert_eq!(release_waiting_ghosts(&mut gate), vec![GhostId(0)]);
+        assert_eq!(eat_dots(&mut gate, 7), vec![GhostId(1)]);
+        assert_eq!(eat_dots(&mut gate, 10), vec![GhostId(2)]);
+        assert_eq!(eat_dots(&mut gate, 15), vec![GhostId(3)]);
+    }
+
+    #[test]
+    fn the_per_ghost_counters_take_over_with_their_old_values_when_the_last_ghost_left() {
+        let mut gate = gate(1);
+        release_waiting_ghosts(&mut gate);
+        eat_dots(&mut gate, 20);
+
+        gate.step(GateStep::CapmanDied);
+        release_waiting_ghosts(&mut gate);
+        assert!(gate.counter.to_string().starts_with("global counter: 0"));
+
+        eat_dots(&mut gate, 32);
+        assert!(gate.all_ghosts_released());
+        assert!(gate.counter.to_string().starts_with("per ghost counter: 0 0 20 0"));
+    }
+
+    #[test]
+    fn a_ghost_released_by_the_timer_is_skipped_by_the_global_counter() {
+        let mut gate = gate(1);
+        gate.step(GateStep::CapmanDied);
+        release_waiting_ghosts(&mut gate);
+
+        assert_eq!(eat_dots(&mut gate, 3), vec![]);
+        assert_eq!(gate.step(GateStep::TimePassed(Duration::from_secs(4))), Some(GhostId(1)));
+        assert_eq!(eat_dots(&mut gate, 14), vec![GhostId(2)]);
+    }
+
+    #[test]
+    fn a_ghost_released_without_the_gate_is_skipped() {
+        let mut gate = gate(1);
+        release_waiting_ghosts(&mut gate);
+
+        assert_eq!(gate.step(GateStep::GhostReleased(GhostId(2))), Some(GhostId(2)));
+        assert!(gate.ghost_can_leave_house(&GhostId(2)));
+        assert_eq!(eat_dots(&mut gate, 59), vec![]);
+        assert_eq!(eat_dots(&mut gate, 1), vec![GhostId(3)]);
+        assert!(gate.all_ghosts_released());
+    }
+
+    #[test]
+    fn a_ghost_released_before_his_turn_is_not_released_again() {
+        let mut gate = gate(1);
+        release_waiting_ghosts(&mut gate);
+
+        assert_eq!(gate.step(GateStep::GhostReleased(GhostId(3))), Some(GhostId(3)));
+        assert_eq!(eat_dots(&mut gate, 30), vec![GhostId(2)]);
+        assert!(gate.all_ghosts_released());
+        assert_eq!(eat_dots(&mut gate, 100), vec![]);
+    }
+
+    #[test]
+    fn releasing_a_released_ghost_or_one_of_another_house_changes_nothing() {
+        let mut gate = gate(1);
+        release_waiting_ghosts(&mut gate);
+
+        assert_eq!(gate.step(GateStep::GhostReleased(GhostId(0))), None);
+        assert_eq!(gate.step(GateStep::GhostReleased(GhostId(7))), None);
+        assert_eq!(eat_dots(&mut gate, 30), vec![GhostId(2)]);
+    }
+
+    #[test]
+    fn a_gate_with_a_single_ghost_releases_him_immediately() {
+        let mut gate = GhostHouseGate::new(vec![GhostId(7)], &spec(1));
+
+        assert_eq!(release_waiting_ghosts(&mut gate), vec![GhostId(7)]);
+        assert!(gate.all_ghosts_released());
     }
 }
@@ -0,0 +1,243 @@
+use crate::common::position::Position;
+use crate::common::Direction;
+use crate::common::Direction::*;
+use crate::ghosts::brain::{AimHint, BrainContext, GhostBrain};
+
+/// Clyde gets scared of capman inside this radius.
+const CLYDE_RADIUS_IN_FIELDS: f32 = 8.0;
+
+/// Blinky directly hunts capman.
+pub struct BlinkyBrain;
+
+impl GhostBrain for BlinkyBrain {
+    fn chase_target(&self, context: &BrainContext) -> Position {
+        context.capman_position
+    }
+}
+
+/// Pinky tries to ambush capman by targeting the position 4 fields in front of him.
+pub struct PinkyBrain;
+
+impl GhostBrain for PinkyBrain {
+    fn chase_target(&self, context: &BrainContext) -> Position {
+        pinky_target(
+            context.capman_position,
+            context.capman_direction,
+            context.arcade_accuracy.up_overflow_bug,
+        )
+    }
+}
+
+/// Return the position 4 fields in front of capman.
+///
+/// With the overflow bug of the arcade, the target is also moved 4 fields to the left if capman faces up.
+pub fn pinky_target(
+    capman_position: Position,
+    capman_direction: Direction,
+    up_overflow_bug: bool,
+) -> Position {
+    position_in_front_of_capman(capman_position, capman_direction, 4, up_overflow_bug)
+}
+
+/// Inky is moving to a field calculated by using capmans and blinkys position.
+///
+/// 1. You take a field capman is facing with two fields distance
+/// 2. You shoot a line from blinkys position trough this field
+/// 3. You double this distance. The field this line is ending on is inkys target.
+pub struct InkyBrain;
+
+impl GhostBrain for InkyBrain {
+    fn chase_target(&self, context: &BrainContext) -> Position {
+        inky_target(
+            context.capman_position,
+            context.capman_direction,
+            context.blinky_position,
+            context.arcade_accuracy.up_overflow_bug,
+        )
+    }
+
+    fn chase_hints(&self, context: &BrainContext) -> Vec<AimHint> {
+        inky_hints(
+            context.capman_position,
+            context.capman_direction,
+            context.blinky_position,
+            context.arcade_accuracy.up_overflow_bug,
+        )
+    }
+}
+
+/// Return the target of inky, based on the field 2 fields in front of capman and blinkys position.
+///
+/// With the overflow bug of the arcade, the field in front of capman is also moved 2 fields to the left if capman faces up.
+pub fn inky_target(
+    capman_position: Position,
+    capman_direction: Direction,
+    blinky_position: Position,
+    up_overflow_bug: bool,
+) -> Position {
+    let position_capman_is_facing = inky_offset(capman_position, capman_direction, up_overflow_bug);
+    let x_diff = position_capman_is_facing.x - blinky_position.x;
+    let y_diff = position_capman_is_facing.y - blinky_position.y;
+    Position::new(
+        blinky_position.x + 2 * x_diff,
+        blinky_position.y + 2 * y_diff,
+    )
+}
+
+/// Return the line from blinky to the target of inky and the field 2 fields in front of capman, which the line
+/// goes through.
+pub fn inky_hints(
+    capman_position: Position,
+    capman_direction: Direction,
+    blinky_position: Position,
+    up_overflow_bug: bool,
+) -> Vec<AimHint> {
+    let offset = inky_offset(capman_position, capman_direction, up_overflow_bug);
+    let target = inky_target(
+        capman_position,
+        capman_direction,
+        blinky_position,
+        up_overflow_bug,
+    );
+    vec![
+        AimHint::Line(blinky_position, target),
+        AimHint::Field(offset),
+    ]
+}
+
+fn inky_offset(
+    capman_position: Position,
+    capman_direction: Direction,
+    up_overflow_bug: bool,
+) -> Position {
+    position_in_front_of_capman(capman_position, capman_direction, 2, up_overflow_bug)
+}
+
+/// Return the position the given number of fields in front of capman.
+///
+/// The arcade stored directions as vectors and calculated this position with 8 bit arithmetic. Because of an overflow,
+/// the up vector also pointed the same number of fields to the left.
+fn position_in_front_of_capman(
+    capman_position: Position,
+    capman_direction: Direction,
+    offset: usize,
+    up_overflow_bug: bool,
+) -> Position {
+    let position = capman_position.get_position_in_direction_with_offset(&capman_direction, offset);
+
+    match (capman_direction, up_overflow_bug) {
+        (Up, true) => Position::new(position.x - offset as isize, position.y),
+        _ => position,
+    }
+}
+
+/// Clyde hunts capman like blinky, but flees to his corner when he gets closer than 8 fields.
+pub struct ClydeBrain;
+
+impl GhostBrain for ClydeBrain {
+    fn chase_target(&self, context: &BrainContext) -> Position {
+        if clyde_is_near_capman(context) {
+            context.corner
+        } else {
+            context.capman_position
+        }
+    }
+
+    fn chase_hints(&self, _context: &BrainContext) -> Vec<AimHint> {
+        vec![AimHint::Radius(CLYDE_RADIUS_IN_FIELDS)]
+    }
+}
+
+fn clyde_is_near_capman(context: &BrainContext) -> bool {
+    let clyde_coordinates = context.ghost_coordinates;
+    let capman_coordinates = context
+        .dimensions
+        .pos_to_vec(&context.capman_position, clyde_coordinates.z);
+    let distance = clyde_coordinates.distance(capman_coordinates);
+    distance < context.dimensions.field() * CLYDE_RADIUS_IN_FIELDS
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::position::Position;
+    use crate::common::Direction::*;
+    use crate::ghosts::brain::classic::{inky_hints, inky_target, pinky_target};
+    use crate::ghosts::brain::AimHint;
+
+    #[test]
+    fn pinky_targets_4_fields_in_front_of_capman() {
+        let capman = Position::new(10, 10);
+
+        assert_eq!(pinky_target(capman, Up, false), Position::new(10, 14));
+        assert_eq!(pinky_target(capman, Down, false), Position::new(10, 6));
+        assert_eq!(pinky_target(capman, Left, false), Position::new(6, 10));
+        assert_eq!(pinky_target(capman, Right, false), Position::new(14, 10));
+    }
+
+    #[test]
+    fn pinky_targets_4_fields_left_too_when_capman_faces_up_with_the_overflow_bug() {
+        let capman = Position::new(10, 10);
+
+        assert_eq!(pinky_target(capman, Up, true), Position::new(6, 14));
+        assert_eq!(pinky_target(capman, Down, true), Position::new(10, 6));
+        assert_eq!(pinky_target(capman, Left, true), Position::new(6, 10));
+        assert_eq!(pinky_target(capman, Right, true), Position::new(14, 10));
+    }
+
+    #[test]
+    fn inky_doubles_the_line_from_blinky_to_2_fields_in_front_of_capman() {
+        let capman = Position::new(10, 10);
+        let blinky = Position::new(8, 8);
+
+        assert_eq!(
+            inky_target(capman, Up, blinky, false),
+            Position::new(12, 16)
+        );
+        assert_eq!(
+            inky_target(capman, Down, blinky, false),
+            Position::new(12, 8)
+        );
+        assert_eq!(
+            inky_target(capman, Left, blinky, false),
+            Position::new(8, 12)
+        );
+        assert_eq!(
+            inky_target(capman, Right, blinky, false),
+            Position::new(16, 12)
+        );
+    }
+
+    #[test]
+    fn inky_uses_the_field_2_fields_left_too_when_capman_faces_up_with_the_overflow_bug() {
+        let capman = Position::new(10, 10);
+        let blinky = Position::new(8, 8);
+
+        assert_eq!(inky_target(capman, Up, blinky, true), Position::new(8, 16));
+        assert_eq!(
+            inky_target(capman, Down, blinky, true),
+            Position::new(12, 8)
+        );
+        assert_eq!(
+            inky_target(capman, Left, blinky, true),
+            Position::new(8, 12)
+        );
+        assert_eq!(
+            inky_target(capman, Right, blinky, true),
+            Position::new(16, 12)
+        );
+    }
+
+    #[test]
+    fn inky_explains_his_target_with_the_line_from_blinky_through_2_fields_in_front_of_capman() {
+        let capman = Position::new(10, 10);
+        let blinky = Position::new(3, 20);
+
+        assert_eq!(
+            inky_hints(capman, Right, blinky, false),
+            vec![
+                AimHint::Line(blinky, Position::new(21, 0)),
+                AimHint::Field(Position::new(12, 10)),
+            ]
+        );
+    }
+}
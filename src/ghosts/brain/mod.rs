@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::board_dimensions::BoardDimensions;
+use crate::common::position::{Neighbour, Position};
+use crate::common::Direction;
+use crate::ghosts::brain::classic::{BlinkyBrain, ClydeBrain, InkyBrain, PinkyBrain};
+use crate::ghosts::brain::pathfinding::PathfindingBrain;
+use crate::ghosts::Ghost::*;
+use crate::map::navigation::NavigationGraph;
+use crate::random::Random;
+
+pub mod classic;
+pub mod pathfinding;
+
+/// Decides where a ghost wants to go. Every ghost carries his brain as a [Brain] component, which he gets
+/// from [GhostBrains] when he spawns.
+///
+/// The target setter asks the brain for a target position and then moves the ghost to the neighbouring
+/// field nearest to it, following the usual ghost rules (no turning around, no walking through walls).
+/// A new kind of ghost just needs a new brain, the target setter stays untouched.
+pub trait GhostBrain: Send + Sync + 'static {
+    /// The position the ghost hunts while in chase.
+    fn chase_target(&self, context: &BrainContext) -> Position;
+
+    /// The position the ghost runs to while in scatter. By default, this is his corner.
+    fn scatter_target(&self, context: &BrainContext) -> Position {
+        context.corner
+    }
+
+    /// Choose one of the given neighbours while frightened. The neighbours are never empty.
+    ///
+    /// By default, a random neighbour is chosen, like in the arcade.
+    fn frightened_choice(
+        &self,
+        _context: &BrainContext,
+        possible_neighbours: &[Neighbour],
+        random: &mut Random,
+    ) -> Neighbour {
+        possible_neighbours[random.zero_to(possible_neighbours.len())]
+    }
+
+    /// The distance between two positions, used to find the neighbour nearest to the target.
+    ///
+    /// By default, this is the (squared) euclidean distance, like in the arcade.
+    fn distance(&self, _context: &BrainContext, from: &Position, to: &Position) -> isize {
+        from.distance_to(to)
+    }
+
+    /// Shapes that explain how the brain chose its chase target, which the target overlay of the debug mode draws.
+    ///
+    /// By default, there are none.
+    fn chase_hints(&self, _context: &BrainContext) -> Vec<AimHint> {
+        vec![]
+    }
+}
+
+/// A shape that explains how a brain chose its target (see [GhostBrain::chase_hints]).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AimHint {
+    /// A field the target is based on.
+    Field(Position),
+    /// A line from one field to another.
+    Line(Position, Position),
+    /// A circle around the ghost with the given radius in fields.
+    Radius(f32),
+}
+
+/// Everything a brain might want to know to make its decision.
+pub struct BrainContext<'a> {
+    pub dimensions: &'a BoardDimensions,
+    pub navigation_graph: &'a NavigationGraph,
+    pub ghost_coordinates: Vec3,
+    /// The corner of the ghost, which he runs to in scatter
+    pub corner: Position,
+    pub capman_position: Position,
+    pub capman_direction: Direction,
+    pub blinky_position: Position,
+    pub arcade_accuracy: ArcadeAccuracy,
+}
+
+/// Settings to reproduce quirks of the original arcade game, which are off by default.
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ArcadeAccuracy {
+    /// In the arcade, pinky and inky also target fields to the left of capman if he faces up,
+    /// because of an overflow when calculating the field in front of him.
+    pub up_overflow_bug: bool,
+}
+
+impl ArcadeAccuracy {
+    /// Passing '--arcade-accuracy' on the command line turns on every arcade quirk.
+    pub fn from_args() -> Self {
+        let arcade_accuracy = std::env::args().any(|arg| arg == "--arcade-accuracy");
+
+        ArcadeAccuracy {
+            up_overflow_bug: arcade_accuracy,
+        }
+    }
+}
+
+/// The brain of a ghost, which decides his chase, scatter and frightened behaviour.
+#[derive(Component, Deref)]
+pub struct Brain(Box<dyn GhostBrain>);
+
+impl Brain {
+    pub fn new(brain: impl GhostBrain) -> Self {
+        Brain(Box::new(brain))
+    }
+}
+
+/// Resource with every brain a map can give its ghosts, by name.
+///
+/// The arcade brains are registered as "blinky", "pinky", "inky" and "clyde". Ghosts without a brain
+/// declared on the map get the one of their kind. New brains are registered with [GhostBrains::register],
+/// before the ghosts spawn.
+pub struct GhostBrains {
+    brains: HashMap<String, Box<dyn Fn(BrainKind) -> Brain + Send + Sync>>,
+}
+
+impl GhostBrains {
+    /// Register a brain under the given name. Every ghost using it gets a new one from the given
+    /// function. With [BrainKind::Pathfinding], the brain gets wrapped in a [PathfindingBrain].
+    pub fn register<B: GhostBrain>(
+        &mut self,
+        name: impl Into<String>,
+        create: impl Fn() -> B + Send + Sync + 'static,
+    ) {
+        let create_brain = move |kind| match kind {
+            BrainKind::Classic => Brain::new(create()),
+            BrainKind::Pathfinding => Brain::new(PathfindingBrain(create())),
+        };
+        self.brains.insert(name.into(), Box::new(create_brain));
+    }
+
+    /// Create the brain with the given name, of the given kind.
+    pub fn create(&self, name: &str, kind: BrainKind) -> Brain {
+        let create_brain = self
+            .brains
+            .get(name)
+            .unwrap_or_else(|| panic!("the map uses the unknown ghost brain '{}'", name));
+        create_brain(kind)
+    }
+}
+
+impl Default for GhostBrains {
+    fn default() -> Self {
+        let mut brains = GhostBrains {
+            brains: HashMap::new(),
+        };
+        brains.register(Blinky.name(), || BlinkyBrain);
+        brains.register(Pinky.name(), || PinkyBrain);
+        brains.register(Inky.name(), || InkyBrain);
+        brains.register(Clyde.name(), || ClydeBrain);
+        brains
+    }
+}
+
+/// Resource that tells which kind of brain the ghosts get when they spawn.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BrainKind {
+    /// The arcade behaviour, where ghosts move to the neighbour with the smallest euclidean distance to their target.
+    Classic,
+    /// The arcade targets, but ghosts measure the true walking distance to them.
+    Pathfinding,
+}
+
+impl BrainKind {
+    /// Passing '--pathfinding' on the command line makes the ghosts use pathfinding brains.
+    pub fn from_args() -> Self {
+        match std::env::args().any(|arg| arg == "--pathfinding") {
+            true => BrainKind::Pathfinding,
+            false => BrainKind::Classic,
+        }
+    }
+}
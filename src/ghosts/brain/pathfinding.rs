@@ -0,0 +1,41 @@
+use crate::common::position::{Neighbour, Position};
+use crate::ghosts::brain::{AimHint, BrainContext, GhostBrain};
+use crate::random::Random;
+
+/// Wraps another brain and keeps its targets, but measures the true walking distance to them.
+///
+/// The euclidean distance of the arcade can lead ghosts into dead ends or loops on some maps. A ghost with
+/// a pathfinding brain always takes the first step of the shortest way to his target.
+pub struct PathfindingBrain<B: GhostBrain>(pub B);
+
+impl<B: GhostBrain> GhostBrain for PathfindingBrain<B> {
+    fn chase_target(&self, context: &BrainContext) -> Position {
+        self.0.chase_target(context)
+    }
+
+    fn scatter_target(&self, context: &BrainContext) -> Position {
+        self.0.scatter_target(context)
+    }
+
+    fn frightened_choice(
+        &self,
+        context: &BrainContext,
+        possible_neighbours: &[Neighbour],
+        random: &mut Random,
+    ) -> Neighbour {
+        self.0.frightened_choice(context, possible_neighbours, random)
+    }
+
+    /// Unreachable targets are infinitely far away.
+    fn distance(&self, context: &BrainContext, from: &Position, to: &Position) -> isize {
+        context
+            .navigation_graph
+            .path_distance(from, to, false)
+            .map(|distance| distance as isize)
+            .unwrap_or(isize::MAX)
+    }
+
+    fn chase_hints(&self, context: &BrainContext) -> Vec<AimHint> {
+        self.0.chase_hints(context)
+    }
+}
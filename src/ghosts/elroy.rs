@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+
+use crate::edibles::dots::EatenDots;
+use crate::ghost_house_gate::GhostHouseGates;
+use crate::ghosts::state::StateSetter;
+use crate::ghosts::target::LTargetSetter;
+use crate::ghosts::GhostId;
+use crate::interactions::ECapmanHit;
+use crate::level::Level;
+use crate::life_cycle::LifeCycle::*;
+use crate::specs_per_level::{Spec, SpecsPerLevel};
+
+pub(in crate::ghosts) struct ElroyPlugin;
+
+impl Plugin for ElroyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ElroySuspension>()
+            .add_system_set(SystemSet::on_enter(Start).with_system(lift_elroy_suspension))
+            .add_system_set(SystemSet::on_enter(LevelTransition).with_system(lift_elroy_suspension))
+            .add_system_set(
+                SystemSet::on_update(Running)
+                    .with_system(update_elroy.after(StateSetter).before(LTargetSetter)),
+            );
+    }
+}
+
+/// Component for ghosts that become "Cruise Elroy" when only a few dots are left (blinky in the arcade).
+///
+/// An elroy gets faster in two levels, depending on the remaining dots and the thresholds of the current spec.
+/// While elroy is active, the ghost keeps chasing capman during scatter.
+///
+/// After capman got hit, the ghost house gates start over (see GhostHouseGate). Elroy is suspended
+/// until the last ghost of his house (clyde in the arcade) was released again, so the ghosts get a chance
+/// to leave their houses before blinky hunts. At the start of a level, elroy is never suspended.
+#[derive(Component, Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Elroy {
+    level: usize,
+    suspended: bool,
+}
+
+impl Elroy {
+    /// The elroy level currently in effect. 0 means elroy is inactive (or suspended).
+    pub fn active_level(&self) -> usize {
+        match self.suspended {
+            true => 0,
+            false => self.level,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active_level() > 0
+    }
+
+    fn update(
+        &mut self,
+        remaining_dots: usize,
+        spec: &Spec,
+        capman_hit: bool,
+        all_ghosts_left_house: bool,
+    ) {
+        self.level = match remaining_dots {
+            r if r <= spec.elroy_2_dots_left => 2,
+            r if r <= spec.elroy_1_dots_left => 1,
+            _ => 0,
+        };
+
+        if capman_hit {
+            self.suspended = true
+        } else if all_ghosts_left_house {
+            self.suspended = false
+        }
+    }
+}
+
+/// Resource that tells if an elroy is still suspended because capman got hit.
+///
+/// Blinky respawns after capman died, so the new elroy needs to know that his predecessor was suspended.
+#[derive(Debug, Default)]
+pub struct ElroySuspension(bool);
+
+fn update_elroy(
+    mut event_reader: EventReader<ECapmanHit>,
+    level: Res<Level>,
+    specs_per_level: Res<SpecsPerLevel>,
+    eaten_dots: Res<EatenDots>,
+    ghost_house_gates: Res<GhostHouseGates>,
+    mut suspension: ResMut<ElroySuspension>,
+    mut query: Query<(&GhostId, &mut Elroy)>,
+) {
+    let spec = specs_per_level.get_for(&level);
+    let capman_hit = event_reader.iter().count() > 0;
+
+    for (id, mut elroy) in &mut query {
+        let suspend = capman_hit || (elroy.is_added() && suspension.0);
+        let all_ghosts_left_house = ghost_house_gates.gate_of(id).all_ghosts_released();
+        elroy.update(
+            eaten_dots.get_remaining(),
+            spec,
+            suspend,
+            all_ghosts_left_house,
+        )
+    }
+
+    suspension.0 = query.iter().any(|(_, elroy)| elroy.suspended);
+}
+
+fn lift_elroy_suspension(mut suspension: ResMut<ElroySuspension>) {
+    suspension.0 = false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ghosts::elroy::Elroy;
+    use crate::specs_per_level::Spec;
+
+    fn spec() -> Spec {
+        Spec {
+            elroy_1_dots_left: 20,
+            elroy_2_dots_left: 10,
+            ..Spec::default()
+        }
+    }
+
+    #[test]
+    fn the_elroy_level_depends_on_the_remaining_dots() {
+        let mut elroy = Elroy::default();
+
+        elroy.update(21, &spec(), false, true);
+        assert_eq!(elroy.active_level(), 0);
+
+        elroy.update(20, &spec(), false, true);
+        assert_eq!(elroy.active_level(), 1);
+
+        elroy.update(10, &spec(), false, true);
+        assert_eq!(elroy.active_level(), 2);
+    }
+
+    #[test]
+    fn elroy_is_suspended_until_all_ghosts_left_the_house() {
+        let mut elroy = Elroy::default();
+
+        elroy.update(5, &spec(), true, false);
+        assert!(!elroy.is_active());
+
+        elroy.update(5, &spec(), false, false);
+        assert!(!elroy.is_active());
+
+        elroy.update(5, &spec(), false, true);
+        assert!(elroy.is_active());
+        assert_eq!(elroy.active_level(), 2);
+    }
+
+    #[test]
+    fn elroy_is_not_suspended_at_the_start_of_a_level() {
+        let mut elroy = Elroy::default();
+
+        elroy.update(5, &spec(), false, false);
+
+        assert!(elroy.is_active());
+        assert_eq!(elroy.active_level(), 2);
+    }
+}
@@ -1,18 +1,25 @@
+use crate::difficulty::Difficulty;
 use crate::game_assets::loaded_assets::LoadedAssets;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
+use crate::ghosts::brain::{BrainKind, GhostBrains};
+use crate::ghosts::elroy::ElroyPlugin;
 use crate::ghosts::movement::MovePlugin;
 use crate::ghosts::schedule::SchedulePlugin;
 use crate::ghosts::spawn::spawn_ghosts;
-use crate::ghosts::state::StatePlugin;
+use crate::ghosts::state::{StatePlugin, StateSetter};
 use crate::ghosts::target::{Target, TargetPlugin};
 use crate::ghosts::textures::{start_animation, update_ghost_appearance};
+use crate::ghosts::Ghost::*;
 use crate::interactions::EGhostEaten;
 use crate::life_cycle::LifeCycle::*;
 use crate::tunnels::GhostPassedTunnel;
 
+pub mod brain;
+pub mod elroy;
 pub mod movement;
-mod schedule;
+pub mod schedule;
 pub mod spawn;
 pub mod state;
 pub mod target;
@@ -22,15 +29,19 @@ pub struct GhostPlugin;
 
 impl Plugin for GhostPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(MovePlugin)
+        app.insert_resource(BrainKind::from_args())
+            .init_resource::<GhostBrains>()
+            .add_startup_system(switch_brains_for_difficulty)
+            .add_plugin(MovePlugin)
             .add_plugin(TargetPlugin)
             .add_plugin(StatePlugin)
             .add_plugin(SchedulePlugin)
+            .add_plugin(ElroyPlugin)
             .add_system_set(SystemSet::on_enter(Ready).with_system(spawn_ghosts))
             .add_system_set(SystemSet::on_enter(Running).with_system(start_animation))
             .add_system_set(
                 SystemSet::on_update(Running)
-                    .with_system(ghost_passed_tunnel)
+                    .with_system(ghost_passed_tunnel.before(StateSetter))
                     .with_system(update_ghost_appearance)
                     .with_system(play_ghost_eaten_sound_when_ghost_was_eaten),
             )
@@ -48,6 +59,12 @@ impl Plugin for GhostPlugin {
     }
 }
 
+fn switch_brains_for_difficulty(difficulty: Res<Difficulty>, mut brain_kind: ResMut<BrainKind>) {
+    if let Some(kind) = difficulty.brain_kind() {
+        *brain_kind = kind
+    }
+}
+
 fn ghost_passed_tunnel(
     mut event_reader: EventReader<GhostPassedTunnel>,
     mut query: Query<(Entity, &mut Target), With<Ghost>>,
@@ -103,7 +120,12 @@ fn play_ghost_eaten_sound_when_ghost_was_eaten(
     }
 }
 
-#[derive(Copy, Clone, Component, Eq, PartialEq, Hash)]
+/// The kind of a ghost, which decides how he looks.
+///
+/// How a ghost behaves is up to his [Brain](brain::Brain). By default, he gets the arcade brain of his kind,
+/// but a map can give him any brain registered in [GhostBrains](brain::GhostBrains). The kinds are only
+/// special in two ways: blinkys can turn into elroy and inky's brain aims relative to the nearest blinky.
+#[derive(Copy, Clone, Component, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Ghost {
     Blinky,
     Pinky,
@@ -111,6 +133,24 @@ pub enum Ghost {
     Clyde,
 }
 
+impl Ghost {
+    /// The name of the ghost, which is also the name of his arcade brain.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Blinky => "blinky",
+            Pinky => "pinky",
+            Inky => "inky",
+            Clyde => "clyde",
+        }
+    }
+}
+
+/// Identifies a single ghost, as a map can contain any number of ghosts, even several of the same kind.
+///
+/// Ghosts are numbered in the order they get released from the ghost house.
+#[derive(Copy, Clone, Component, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct GhostId(pub usize);
+
 /// Resource that holds the entity id of the ghost that is currently eaten by capman
 #[derive(Deref)]
 pub struct CurrentlyEatenGhost(pub Entity);
@@ -14,20 +14,34 @@ pub struct MovePlugin;
 impl Plugin for MovePlugin {
     fn build(&self, app: &mut App) {
         app
+            .init_resource::<GhostsFrozen>()
             .add_system_set(
-                SystemSet::on_update(Running).with_system(move_ghosts.after(LTargetSetter))
+                SystemSet::on_update(Running).with_system(move_ghosts.label(LGhostMovement).after(LTargetSetter))
             )
             .add_system_set(
-                SystemSet::on_update(GhostEatenPause).with_system(move_only_not_currently_eaten_ghosts.after(LTargetSetter))
+                SystemSet::on_update(GhostEatenPause).with_system(move_only_not_currently_eaten_ghosts.label(LGhostMovement).after(LTargetSetter))
             )
         ;
     }
 }
 
+/// Marks every system that moves the ghosts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct LGhostMovement;
+
+/// If true, no ghost moves. They still change their state and target.
+#[derive(Debug, Default, Deref, DerefMut)]
+pub struct GhostsFrozen(pub bool);
+
 fn move_ghosts(
     time: Res<Time>,
+    ghosts_frozen: Res<GhostsFrozen>,
     mut query: Query<(&Direction, &mut Target, &mut Transform, &Speed)>,
 ) {
+    if **ghosts_frozen {
+        return;
+    }
+
     for (direction, mut target, mut transform, speed) in query.iter_mut() {
         move_ghost(&time, direction, &mut target, &mut transform, speed)
     }
@@ -36,8 +50,13 @@ fn move_ghosts(
 fn move_only_not_currently_eaten_ghosts(
     time: Res<Time>,
     currently_eaten_ghost: Res<CurrentlyEatenGhost>,
+    ghosts_frozen: Res<GhostsFrozen>,
     mut query: Query<(Entity, &Direction, &State, &mut Target, &mut Transform, &Speed)>,
 ) {
+    if **ghosts_frozen {
+        return;
+    }
+
     for (entity, direction, state, mut target, mut transform, speed) in query.iter_mut() {
         if entity == **currently_eaten_ghost || *state != Eaten { continue; }
         move_ghost(&time, direction, &mut target, &mut transform, speed)
@@ -1,9 +1,10 @@
 use bevy::prelude::*;
 use bevy::utils::{Duration, HashMap};
-use crate::edibles::energizer::EnergizerTimer;
+use crate::difficulty::Difficulty;
+use crate::edibles::energizer::{EnergizerTimer, LEnergizerTimer};
 use crate::life_cycle::LifeCycle::*;
 use crate::level::Level;
-use crate::ghosts::state::State;
+use crate::ghosts::state::{State, StateSetter};
 use crate::ghosts::state::State::*;
 use crate::life_cycle::LifeCycle::Start;
 
@@ -13,18 +14,28 @@ impl Plugin for SchedulePlugin {
     fn build(&self, app: &mut App) {
         app
             .insert_resource(ScheduleByLevel::new())
+            .add_startup_system(scale_scatter_phases_by_difficulty)
             .add_system_set(
                 SystemSet::on_enter(Start).with_system(register_start_schedule)
             )
             .add_system_set(
                 SystemSet::on_update(Running)
                     .with_system(switch_schedule_when_level_changed)
-                    .with_system(update_schedule)
+                    .with_system(update_schedule.after(switch_schedule_when_level_changed))
+                    .after(LEnergizerTimer)
+                    .before(StateSetter)
             )
         ;
     }
 }
 
+fn scale_scatter_phases_by_difficulty(
+    difficulty: Res<Difficulty>,
+    mut schedule_by_level: ResMut<ScheduleByLevel>,
+) {
+    schedule_by_level.scale_phases(Scatter, difficulty.scatter_time_factor())
+}
+
 fn register_start_schedule(
     mut commands: Commands,
     level: Res<Level>,
@@ -51,8 +62,9 @@ fn update_schedule(
     energizer_timer: Option<Res<EnergizerTimer>>,
     mut schedule: ResMut<Schedule>,
 ) {
-    if energizer_timer.is_none() {
-        schedule.update(time.delta());
+    match energizer_timer {
+        None => schedule.update(time.delta()),
+        Some(_) => schedule.freeze(time.delta()),
     }
 }
 
@@ -62,7 +74,7 @@ pub struct ScheduleByLevel {
 }
 
 impl ScheduleByLevel {
-    fn new() -> Self {
+    pub(in crate::ghosts) fn new() -> Self {
         ScheduleByLevel {
             level_schedule_map: [
                 (Level(1), Self::level_one()),
@@ -116,13 +128,27 @@ impl ScheduleByLevel {
     pub fn get_schedule_for_level(&self, level: &Level) -> Schedule {
         self.level_schedule_map.get(level).unwrap_or(&self.default_schedule).clone()
     }
+
+    /// Replace the schedule for the given level, for example to adjust the difficulty while the game is running.
+    pub fn set_schedule_for_level(&mut self, level: Level, schedule: Schedule) {
+        self.level_schedule_map.insert(level, schedule);
+    }
+
+    /// Scale the length of every phase with the given state in every schedule.
+    fn scale_phases(&mut self, state: State, factor: f32) {
+        for schedule in self.level_schedule_map.values_mut().chain([&mut self.default_schedule]) {
+            schedule.scale_phases(state, factor)
+        }
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Schedule {
     current_phase_index: usize,
     current_phase_timer: Option<Timer>,
     phases: Vec<Phase>,
+    /// The time the schedule did not proceed because an energizer was active.
+    frozen_time: Duration,
 }
 
 impl Schedule {
@@ -133,6 +159,7 @@ impl Schedule {
             current_phase_index: 0,
             current_phase_timer: phases.get(0).expect("at least one phase must be provided").phase_timer(),
             phases,
+            frozen_time: Duration::ZERO,
         }
     }
 
@@ -140,6 +167,28 @@ impl Schedule {
         self.phases[self.current_phase_index].state
     }
 
+    pub fn phases(&self) -> &[Phase] {
+        &self.phases
+    }
+
+    pub fn current_phase_index(&self) -> usize {
+        self.current_phase_index
+    }
+
+    /// Seconds spent in the current phase.
+    pub fn elapsed_in_current_phase(&self) -> f32 {
+        self.current_phase_timer.as_ref().map_or(0.0, Timer::elapsed_secs)
+    }
+
+    pub fn frozen_time(&self) -> Duration {
+        self.frozen_time
+    }
+
+    /// Keep track of the time the schedule stands still.
+    pub fn freeze(&mut self, delta: Duration) {
+        self.frozen_time += delta
+    }
+
     pub fn update(&mut self, delta: Duration) {
         if let Some(ref mut timer) = self.current_phase_timer {
             timer.tick(delta);
@@ -150,6 +199,22 @@ impl Schedule {
         }
     }
 
+    /// Scale the length of every phase with the given state.
+    ///
+    /// The time already spent in the current phase is kept.
+    pub fn scale_phases(&mut self, state: State, factor: f32) {
+        for phase in self.phases.iter_mut().filter(|p| p.state == state) {
+            phase.time = phase.time.map(|t| t * factor)
+        }
+
+        let elapsed = self.current_phase_timer.as_ref().map(Timer::elapsed);
+        self.current_phase_timer = self.phases[self.current_phase_index].phase_timer();
+
+        if let (Some(timer), Some(elapsed)) = (self.current_phase_timer.as_mut(), elapsed) {
+            timer.set_elapsed(elapsed)
+        }
+    }
+
     fn switch_to_next_phase(&mut self) {
         if self.current_phase_index < self.phases.len() - 1 {
             self.current_phase_index += 1;
@@ -158,7 +223,7 @@ impl Schedule {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Phase {
     state: State,
     time: Option<f32>,
@@ -179,6 +244,15 @@ impl Phase {
         }
     }
 
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// The length of the phase in seconds. None if the phase never ends.
+    pub fn seconds(&self) -> Option<f32> {
+        self.time
+    }
+
     fn phase_timer(&self) -> Option<Timer> {
         Some(Timer::from_seconds(self.time?, false))
     }
@@ -3,13 +3,18 @@ use crate::board_dimensions::BoardDimensions;
 
 use crate::constants::{BLINKY_Z, CLYDE_Z, INKY_Z, PINKY_Z};
 use crate::game_assets::loaded_assets::LoadedAssets;
-use crate::ghost_house::GhostHouse;
+use crate::ghost_house::{CurrentHouse, GhostHouse, GhostHouses};
+use crate::ghosts::brain::{Brain, BrainKind, GhostBrains};
+use crate::ghosts::elroy::Elroy;
 use crate::ghosts::Ghost;
 use crate::ghosts::Ghost::*;
 use crate::ghosts::state::State;
 use crate::ghosts::target::Target;
 use crate::ghosts::textures::create_animations_for_ghost;
 use crate::level::Level;
+use crate::map::{GhostDeclaration, Map};
+use crate::players::input::{InputBuffer, KeyBindings};
+use crate::players::{GameMode, Player};
 use crate::specs_per_level::SpecsPerLevel;
 use crate::speed::Speed;
 use crate::sprite_sheet::SpriteSheet;
@@ -18,15 +23,26 @@ pub fn spawn_ghosts(
     mut commands: Commands,
     game_assets: Res<LoadedAssets>,
     sprite_sheets: Res<Assets<SpriteSheet>>,
-    ghost_house: Res<GhostHouse>,
+    ghost_houses: Res<GhostHouses>,
+    map: Res<Map>,
     level: Res<Level>,
+    game_mode: Res<GameMode>,
+    brain_kind: Res<BrainKind>,
+    ghost_brains: Res<GhostBrains>,
     specs_per_level: Res<SpecsPerLevel>,
     dimensions: Res<BoardDimensions>
 ) {
-    spawn_ghost(&mut commands, &ghost_house, &game_assets, &sprite_sheets, &level, &specs_per_level, Blinky, &dimensions, BLINKY_Z);
-    spawn_ghost(&mut commands, &ghost_house, &game_assets, &sprite_sheets, &level, &specs_per_level, Pinky, &dimensions, PINKY_Z);
-    spawn_ghost(&mut commands, &ghost_house, &game_assets, &sprite_sheets, &level, &specs_per_level, Inky, &dimensions, INKY_Z);
-    spawn_ghost(&mut commands, &ghost_house, &game_assets, &sprite_sheets, &level, &specs_per_level, Clyde, &dimensions, CLYDE_Z);
+    let ghosts = map.ghosts();
+
+    for declaration in &ghosts {
+        // a player only controls the first ghost of a kind
+        let controlled_by = match ghosts.iter().find(|g| g.kind == declaration.kind) {
+            Some(first) if first.id == declaration.id => game_mode.player_controlling(&declaration.kind),
+            _ => None
+        };
+        let brain = ghost_brains.create(&declaration.brain, *brain_kind);
+        spawn_ghost(&mut commands, ghost_houses.house_of(&declaration.id), &game_assets, &sprite_sheets, &level, controlled_by, game_mode.as_ref(), brain, &specs_per_level, declaration, &dimensions);
+    }
 }
 
 fn spawn_ghost(
@@ -35,19 +51,22 @@ fn spawn_ghost(
     game_assets: &LoadedAssets,
     sprite_sheets: &Assets<SpriteSheet>,
     level: &Level,
+    controlled_by: Option<Player>,
+    game_mode: &GameMode,
+    brain: Brain,
     specs_per_level: &SpecsPerLevel,
-    ghost: Ghost,
+    declaration: &GhostDeclaration,
     dimensions: &BoardDimensions,
-    z_value: f32
 ) {
-    let spawn_direction = ghost_house.spawn_direction_of(&ghost);
-    let mut spawn_coordinates = ghost_house.spawn_coordinates_of(&ghost);
-    spawn_coordinates.z = z_value;
+    let ghost = declaration.kind;
+    let spawn_direction = ghost_house.spawn_direction_of(&declaration.id);
+    let mut spawn_coordinates = ghost_house.spawn_coordinates_of(&declaration.id);
+    spawn_coordinates.z = z_value_of(&ghost);
     let mut animations = create_animations_for_ghost(&ghost, game_assets, sprite_sheets);
     animations.stop();
 
-    commands
-        .spawn()
+    let mut entity = commands.spawn();
+    entity
         .insert_bundle(SpriteBundle {
             texture: animations.current().texture(),
             sprite: Sprite {
@@ -58,10 +77,33 @@ fn spawn_ghost(
             ..Default::default()
         })
         .insert(ghost)
+        .insert(declaration.id)
+        .insert(CurrentHouse(declaration.house))
+        .insert(brain)
         .insert(spawn_direction)
         .insert(Speed(dimensions.ghost_base_speed() * specs_per_level.get_for(level).ghost_normal_speed_modifier))
         .insert(Target::new())
         .insert(State::Spawned)
         .insert(animations)
     ;
-}
\ No newline at end of file
+
+    if ghost == Blinky {
+        entity.insert(Elroy::default());
+    }
+
+    if let Some(player) = controlled_by {
+        entity
+            .insert(player)
+            .insert(KeyBindings::for_player(player, game_mode))
+            .insert(InputBuffer::default());
+    }
+}
+
+fn z_value_of(ghost: &Ghost) -> f32 {
+    match ghost {
+        Blinky => BLINKY_Z,
+        Pinky => PINKY_Z,
+        Inky => INKY_Z,
+        Clyde => CLYDE_Z,
+    }
+}
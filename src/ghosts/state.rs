@@ -2,35 +2,42 @@ use crate::board_dimensions::BoardDimensions;
 use bevy::ecs::event::Event;
 use bevy::ecs::query::WorldQuery;
 use bevy::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Formatter;
 
 use crate::common::Direction;
 use crate::common::XYEqual;
 use crate::edibles::energizer::EnergizerOver;
-use crate::ghost_house::GhostHouse;
+use crate::ghost_house::{CurrentHouse, GhostHouse, GhostHouses};
 use crate::ghosts::schedule::Schedule;
 use crate::ghosts::state::State::*;
 use crate::ghosts::target::Target;
-use crate::ghosts::Ghost;
+use crate::ghosts::{Ghost, GhostId};
 use crate::interactions::{
     EEnergizerEaten, EGhostEaten, LCapmanEnergizerHitDetection, LCapmanGhostHitDetection,
 };
+use crate::level::Level;
 use crate::life_cycle::LifeCycle::*;
+use crate::specs_per_level::SpecsPerLevel;
 
 pub struct StatePlugin;
 
 impl Plugin for StatePlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(
-            SystemSet::on_update(Running)
-                .with_system(update_state)
-                .after(LCapmanGhostHitDetection)
-                .after(LCapmanEnergizerHitDetection)
-                .label(StateSetter),
-        )
-        .add_system_set(
-            SystemSet::on_update(GhostEatenPause).with_system(update_state_on_eaten_pause),
-        );
+        app.add_event::<EGhostStateChanged>()
+            .init_resource::<StateOverrides>()
+            .add_system_set(
+                SystemSet::on_update(Running)
+                    .with_system(update_state)
+                    .after(LCapmanGhostHitDetection)
+                    .after(LCapmanEnergizerHitDetection)
+                    .label(StateSetter),
+            )
+            .add_system_set(
+                SystemSet::on_update(GhostEatenPause)
+                    .with_system(update_state_on_eaten_pause)
+                    .label(StateSetter),
+            );
     }
 }
 
@@ -46,61 +53,175 @@ pub enum State {
     Spawned,
 }
 
+/// Fired for every state change of a ghost and for every reversal the ghost was forced to.
+///
+/// A reversal does not always come with a new state (for example when an energizer gets eaten on a
+/// level without frightened time). Then from and to are the same.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct EGhostStateChanged {
+    pub entity: Entity,
+    pub ghost: Ghost,
+    pub from: State,
+    pub to: State,
+    pub reason: StateChangeReason,
+}
+
+/// Why a ghost changed his state.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum StateChangeReason {
+    /// The ghost reached the entrance after leaving the ghost house.
+    LeftHouse,
+    /// The schedule switched between scatter and chase. The ghost reverses.
+    ScheduleChanged,
+    /// Capman ate an energizer. The ghost reverses and gets frightened if the level allows it.
+    EnergizerEaten,
+    /// The energizer wore off and the frightened ghost returns to the schedule.
+    EnergizerOver,
+    /// Capman ate the frightened ghost.
+    GhostEaten,
+    /// The eaten ghost reached his respawn in the ghost house.
+    ReachedHouse,
+    /// The state was forced through the StateOverrides. The ghost reverses.
+    Forced,
+    /// The override of the ghost was removed and he returns to the schedule. The ghost reverses.
+    OverrideRemoved,
+}
+
+/// Resource that forces ghosts into a state, regardless of the schedule and energizers. Used to test the ghost AI.
+///
+/// An override for a single ghost wins over the global one. Overrides only apply to ghosts outside the house
+/// which were not eaten, so eaten ghosts still return home. Once the override of a ghost is removed, he
+/// returns to the current state of the schedule.
+#[derive(Debug, Default)]
+pub struct StateOverrides {
+    global: Option<State>,
+    per_ghost: BTreeMap<GhostId, State>,
+    /// The ghosts which were forced into a state by an override which still exists
+    forced_ghosts: BTreeSet<GhostId>,
+}
+
+impl StateOverrides {
+    pub fn force_all(&mut self, state: State) {
+        self.per_ghost.clear();
+        self.global = Some(state)
+    }
+
+    pub fn force(&mut self, ghost: GhostId, state: State) {
+        self.per_ghost.insert(ghost, state);
+    }
+
+    pub fn clear(&mut self) {
+        self.global = None;
+        self.per_ghost.clear()
+    }
+
+    pub fn global(&self) -> Option<State> {
+        self.global
+    }
+
+    pub fn of(&self, ghost: &GhostId) -> Option<State> {
+        self.per_ghost.get(ghost).copied().or(self.global)
+    }
+
+    /// Return true once for a ghost that was forced into a state whose override was removed since.
+    fn override_removed(&mut self, ghost: &GhostId) -> bool {
+        self.of(ghost).is_none() && self.forced_ghosts.remove(ghost)
+    }
+}
+
 #[derive(WorldQuery)]
 #[world_query(mutable)]
 struct StateUpdateComponents<'a> {
     entity: Entity,
     ghost: &'a Ghost,
+    id: &'a GhostId,
     state: &'a mut State,
     target: &'a mut Target,
     direction: &'a mut Direction,
     transform: &'a Transform,
+    house: &'a CurrentHouse,
 }
 
 fn update_state(
     schedule: Res<Schedule>,
-    ghost_house: Res<GhostHouse>,
+    ghost_houses: Res<GhostHouses>,
     dimensions: Res<BoardDimensions>,
+    level: Res<Level>,
+    specs_per_level: Res<SpecsPerLevel>,
+    mut state_overrides: ResMut<StateOverrides>,
     energizer_over_events: EventReader<EnergizerOver>,
     energizer_eaten_events: EventReader<EEnergizerEaten>,
     ghost_eaten_events: EventReader<EGhostEaten>,
+    mut state_changed_events: EventWriter<EGhostStateChanged>,
     mut query: Query<StateUpdateComponents, With<Ghost>>,
 ) {
     let energizer_eaten = energizer_eaten(energizer_eaten_events);
     let energizer_over = energizer_over(energizer_over_events);
     let ghost_eaten_events = collect_events(ghost_eaten_events);
+    let frightened = specs_per_level.get_for(&level).frightened_time > 0.0;
 
     for mut components in &mut query {
-        if ghost_eaten(components.entity, &ghost_eaten_events) {
-            *components.state = Eaten;
-            continue;
-        }
+        let from = *components.state;
+        let forced_state = state_overrides.of(components.id);
 
-        if energizer_eaten && matches!(*components.state, Chase | Scatter) {
-            process_energizer_eaten(&dimensions, &mut components);
-            continue;
-        }
+        let reason = if ghost_eaten(components.entity, &ghost_eaten_events) {
+            *components.state = Eaten;
+            Some(StateChangeReason::GhostEaten)
+        } else if let (Some(forced_state), Scatter | Chase | Frightened) = (forced_state, from) {
+            state_overrides.forced_ghosts.insert(*components.id);
+            process_forced(&dimensions, forced_state, &mut components)
+        } else if state_overrides.override_removed(components.id)
+            && matches!(from, Scatter | Chase | Frightened)
+        {
+            process_override_removed(&schedule, &dimensions, &mut components)
+        } else if energizer_eaten && matches!(*components.state, Chase | Scatter) {
+            process_energizer_eaten(&dimensions, frightened, &mut components)
+        } else {
+            match *components.state {
+                Spawned => process_spawned(&schedule, ghost_houses.get(components.house), &mut components),
+                Scatter | Chase => process_scatter_chase(&schedule, &dimensions, &mut components),
+                Frightened => process_frightened(&schedule, energizer_over, &mut components),
+                Eaten => process_eaten(ghost_houses.get(components.house), &mut components),
+            }
+        };
 
-        match *components.state {
-            Spawned => process_spawned(&schedule, &ghost_house, &mut components),
-            Scatter | Chase => process_scatter_chase(&schedule, &dimensions, &mut components),
-            Frightened => process_frightened(&schedule, energizer_over, &mut components),
-            Eaten => process_eaten(&ghost_house, &mut components),
-        }
+        send_state_changed(&mut state_changed_events, &components, from, reason)
     }
 }
 
 fn update_state_on_eaten_pause(
     schedule: Res<Schedule>,
-    ghost_house: Res<GhostHouse>,
+    ghost_houses: Res<GhostHouses>,
+    mut state_changed_events: EventWriter<EGhostStateChanged>,
     mut query: Query<StateUpdateComponents, With<Ghost>>,
 ) {
     for mut components in &mut query {
-        match *components.state {
-            Spawned => process_spawned(&schedule, &ghost_house, &mut components),
-            Eaten => process_eaten(&ghost_house, &mut components),
+        let from = *components.state;
+
+        let reason = match *components.state {
+            Spawned => process_spawned(&schedule, ghost_houses.get(components.house), &mut components),
+            Eaten => process_eaten(ghost_houses.get(components.house), &mut components),
             _ => continue,
-        }
+        };
+
+        send_state_changed(&mut state_changed_events, &components, from, reason)
+    }
+}
+
+fn send_state_changed(
+    event_writer: &mut EventWriter<EGhostStateChanged>,
+    components: &StateUpdateComponentsItem,
+    from: State,
+    reason: Option<StateChangeReason>,
+) {
+    if let Some(reason) = reason {
+        event_writer.send(EGhostStateChanged {
+            entity: components.entity,
+            ghost: *components.ghost,
+            from,
+            to: *components.state,
+            reason,
+        })
     }
 }
 
@@ -120,10 +241,53 @@ fn ghost_eaten(entity: Entity, eaten_events: &Vec<EGhostEaten>) -> bool {
     eaten_events.iter().filter(|e| e.0 == entity).count() > 0
 }
 
+/// Ghosts always reverse when an energizer gets eaten, but only get frightened if the level has frightened time.
 fn process_energizer_eaten(
     dimensions: &BoardDimensions,
+    frightened: bool,
     components: &mut StateUpdateComponentsItem,
-) {
+) -> Option<StateChangeReason> {
+    if frightened {
+        *components.state = State::Frightened;
+    }
+    reverse(dimensions, components);
+    Some(StateChangeReason::EnergizerEaten)
+}
+
+/// Ghosts change into the forced state and reverse, like on a schedule change.
+fn process_forced(
+    dimensions: &BoardDimensions,
+    forced_state: State,
+    components: &mut StateUpdateComponentsItem,
+) -> Option<StateChangeReason> {
+    if *components.state == forced_state {
+        return None;
+    }
+
+    *components.state = forced_state;
+    reverse(dimensions, components);
+    Some(StateChangeReason::Forced)
+}
+
+/// Ghosts return to the schedule and reverse, like on a schedule change.
+fn process_override_removed(
+    schedule: &Schedule,
+    dimensions: &BoardDimensions,
+    components: &mut StateUpdateComponentsItem,
+) -> Option<StateChangeReason> {
+    let schedule_state = schedule.current_state();
+
+    if *components.state == schedule_state {
+        return None;
+    }
+
+    *components.state = schedule_state;
+    reverse(dimensions, components);
+    Some(StateChangeReason::OverrideRemoved)
+}
+
+/// Let the ghost turn around and move back to the position he came from.
+fn reverse(dimensions: &BoardDimensions, components: &mut StateUpdateComponentsItem) {
     let target_coordinates = if components.target.is_set() {
         components.target.get()
     } else {
@@ -137,7 +301,6 @@ fn process_energizer_eaten(
         0.0,
     );
 
-    *components.state = State::Frightened;
     components.direction.reverse();
     components.target.set(coordinates_ghost_came_from);
 }
@@ -146,12 +309,14 @@ fn process_spawned(
     schedule: &Schedule,
     ghost_house: &GhostHouse,
     components: &mut StateUpdateComponentsItem,
-) {
+) -> Option<StateChangeReason> {
     let coordinates = components.transform.translation;
     if coordinates.xy_equal_to(&ghost_house.coordinates_in_front_of_entrance()) {
         *components.state = schedule.current_state();
         *components.direction = ghost_house.entrance_direction.rotate_left();
+        return Some(StateChangeReason::LeftHouse);
     }
+    None
 }
 
 /// If the current schedule is different to the ghosts state, the new state is the current schedule and
@@ -160,47 +325,40 @@ fn process_scatter_chase(
     schedule: &Schedule,
     dimensions: &BoardDimensions,
     components: &mut StateUpdateComponentsItem,
-) {
+) -> Option<StateChangeReason> {
     let schedule_state = schedule.current_state();
 
     if let (Chase, Scatter) | (Scatter, Chase) = (*components.state, schedule_state) {
         *components.state = schedule_state;
-
-        let target_coordinates = if components.target.is_set() {
-            components.target.get()
-        } else {
-            components.transform.translation
-        };
-
-        let target_position = dimensions.vec_to_pos(&target_coordinates);
-        let coordinates_ghost_came_from = dimensions.pos_to_vec(
-            &target_position
-                .get_neighbour_in_direction(&components.direction.opposite())
-                .position,
-            0.0,
-        );
-
-        components.direction.reverse();
-        components.target.set(coordinates_ghost_came_from);
+        reverse(dimensions, components);
+        return Some(StateChangeReason::ScheduleChanged);
     }
+    None
 }
 
 fn process_frightened(
     schedule: &Schedule,
     energizer_over: bool,
     components: &mut StateUpdateComponentsItem,
-) {
+) -> Option<StateChangeReason> {
     if energizer_over {
-        *components.state = schedule.current_state()
+        *components.state = schedule.current_state();
+        return Some(StateChangeReason::EnergizerOver);
     }
+    None
 }
 
-fn process_eaten(ghost_house: &GhostHouse, components: &mut StateUpdateComponentsItem) {
+fn process_eaten(
+    ghost_house: &GhostHouse,
+    components: &mut StateUpdateComponentsItem,
+) -> Option<StateChangeReason> {
     let coordinates = components.transform.translation;
 
-    if coordinates.xy_equal_to(&ghost_house.respawn_coordinates_of(components.ghost)) {
-        *components.state = Spawned
+    if coordinates.xy_equal_to(&ghost_house.respawn_coordinates_of(components.id)) {
+        *components.state = Spawned;
+        return Some(StateChangeReason::ReachedHouse);
     }
+    None
 }
 
 impl std::fmt::Display for State {
@@ -237,3 +395,132 @@ macro_rules! state_skip_if {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::board_dimensions::BoardDimensions;
+    use crate::common::position::Position;
+    use crate::common::Direction;
+    use crate::edibles::energizer::EnergizerOver;
+    use crate::ghost_house::{CurrentHouse, GhostHouses};
+    use crate::ghosts::schedule::ScheduleByLevel;
+    use crate::ghosts::state::State::*;
+    use crate::ghosts::state::{
+        update_state, EGhostStateChanged, State, StateChangeReason, StateOverrides,
+    };
+    use crate::ghosts::target::Target;
+    use crate::ghosts::Ghost::Blinky;
+    use crate::ghosts::GhostId;
+    use crate::interactions::{EEnergizerEaten, EGhostEaten};
+    use crate::level::Level;
+    use crate::map::board::Board;
+    use crate::map::Map;
+    use crate::specs_per_level::SpecsPerLevel;
+
+    fn create_world(level: usize) -> World {
+        let map = Map::load_default();
+        let dimensions = BoardDimensions::new(&Board::new(&map));
+        let mut world = World::new();
+        world.insert_resource(ScheduleByLevel::new().get_schedule_for_level(&Level(level)));
+        world.insert_resource(GhostHouses::new(&map, &dimensions));
+        world.insert_resource(dimensions);
+        world.insert_resource(Level(level));
+        world.insert_resource(SpecsPerLevel::arcade());
+        world.insert_resource(StateOverrides::default());
+        world.insert_resource(Events::<EnergizerOver>::default());
+        world.insert_resource(Events::<EEnergizerEaten>::default());
+        world.insert_resource(Events::<EGhostEaten>::default());
+        world.insert_resource(Events::<EGhostStateChanged>::default());
+        world
+    }
+
+    fn spawn_ghost(world: &mut World, state: State) -> Entity {
+        let coordinates = world.resource::<BoardDimensions>().pos_to_vec(&Position::new(1, 1), 0.0);
+        world
+            .spawn()
+            .insert_bundle((Blinky, GhostId(0), CurrentHouse(0), state, Target::new(), Direction::Left))
+            .insert(Transform::from_translation(coordinates))
+            .id()
+    }
+
+    fn update_and_collect_events(world: &mut World) -> Vec<EGhostStateChanged> {
+        SystemStage::single(update_state).run(world);
+
+        let events = world.resource::<Events<EGhostStateChanged>>();
+        events.get_reader().iter(events).copied().collect()
+    }
+
+    #[test]
+    fn nothing_is_fired_without_a_state_change() {
+        let mut world = create_world(1);
+        spawn_ghost(&mut world, Scatter);
+
+        assert_eq!(update_and_collect_events(&mut world), vec![]);
+    }
+
+    #[test]
+    fn an_eaten_energizer_fires_the_change_to_frightened() {
+        let mut world = create_world(1);
+        let ghost = spawn_ghost(&mut world, Scatter);
+        world.resource_mut::<Events<EEnergizerEaten>>().send(EEnergizerEaten(ghost));
+
+        let events = update_and_collect_events(&mut world);
+
+        assert_eq!(
+            events,
+            vec![EGhostStateChanged {
+                entity: ghost,
+                ghost: Blinky,
+                from: Scatter,
+                to: Frightened,
+                reason: StateChangeReason::EnergizerEaten,
+            }]
+        );
+        assert_eq!(world.get::<Direction>(ghost), Some(&Direction::Right));
+    }
+
+    #[test]
+    fn a_reversal_without_a_new_state_is_fired_as_well() {
+        // the arcade has no frightened time on level 17
+        let mut world = create_world(17);
+        let ghost = spawn_ghost(&mut world, Scatter);
+        world.resource_mut::<Events<EEnergizerEaten>>().send(EEnergizerEaten(ghost));
+
+        let events = update_and_collect_events(&mut world);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].from, events[0].to), (Scatter, Scatter));
+    }
+
+    #[test]
+    fn a_forced_state_wins_over_the_schedule_but_not_over_the_ghost_house() {
+        let mut world = create_world(1);
+        let ghost = spawn_ghost(&mut world, Scatter);
+        let spawned_ghost = spawn_ghost(&mut world, Spawned);
+        world.resource_mut::<StateOverrides>().force_all(Chase);
+
+        let events = update_and_collect_events(&mut world);
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].reason, StateChangeReason::Forced);
+        assert_eq!(world.get::<State>(ghost), Some(&Chase));
+        assert_eq!(world.get::<State>(spawned_ghost), Some(&Spawned));
+    }
+
+    #[test]
+    fn a_ghost_returns_to_the_schedule_when_his_override_is_removed() {
+        let mut world = create_world(1);
+        let ghost = spawn_ghost(&mut world, Scatter);
+        world.resource_mut::<StateOverrides>().force(GhostId(0), Frightened);
+        update_and_collect_events(&mut world);
+        assert_eq!(world.get::<State>(ghost), Some(&Frightened));
+
+        world.resource_mut::<StateOverrides>().clear();
+        let events = update_and_collect_events(&mut world);
+
+        assert_eq!(world.get::<State>(ghost), Some(&Scatter));
+        assert_eq!(events.last().map(|e| e.reason), Some(StateChangeReason::OverrideRemoved));
+    }
+}
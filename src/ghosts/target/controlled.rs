@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use crate::ghosts::target::TargetSetter;
+use crate::ghosts::Ghost;
+use crate::players::input::{get_wished_direction, InputBuffer, PlayerInputs};
+use crate::players::Player;
+
+/// Buffer the direction the player wants their ghost to take next.
+///
+/// A ghost only decides where to go when he reached his current target, so the input is kept
+/// until the ghost can actually use it, exactly like capmans buffered turns.
+pub(in crate::ghosts::target) fn buffer_input_of_player_controlled_ghosts(
+    player_inputs: Res<PlayerInputs>,
+    mut query: Query<(&Player, &mut InputBuffer), With<Ghost>>,
+) {
+    for (player, mut input_buffer) in &mut query {
+        **input_buffer = get_wished_direction(&player_inputs, player, &input_buffer);
+    }
+}
+
+impl<'a, 'b, 'c> TargetSetter<'a, 'b, 'c> {
+    /// Determine the next target for a ghost that is controlled by a player.
+    ///
+    /// The ghost takes the buffered direction if the next field in this direction is free.
+    /// Otherwise, he keeps going straight or, if this is blocked too, takes the first free way.
+    /// Like every other ghost, he is not allowed to turn around, unless he has no other way to go.
+    pub fn set_player_controlled_target(&mut self) {
+        let possible_neighbours = self.get_possible_neighbours();
+        let wished_direction = self
+            .components
+            .input_buffer
+            .as_ref()
+            .and_then(|buffer| ***buffer);
+        let current_direction = *self.components.direction;
+
+        let wished_neighbour = possible_neighbours
+            .iter()
+            .find(|n| Some(n.direction) == wished_direction);
+
+        let next_target_neighbour = match wished_neighbour {
+            Some(neighbour) => {
+                if let Some(ref mut buffer) = self.components.input_buffer {
+                    ***buffer = None;
+                }
+                *neighbour
+            }
+            None => possible_neighbours
+                .iter()
+                .find(|n| n.direction == current_direction)
+                .or_else(|| possible_neighbours.first())
+                .copied()
+                .unwrap_or_else(|| self.neighbour_behind()),
+        };
+
+        self.set_target_to_neighbour(next_target_neighbour)
+    }
+}
@@ -0,0 +1,579 @@
+use crate::board_dimensions::BoardDimensions;
+use bevy::ecs::query::WorldQuery;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::capman::{Capman, Dying};
+use crate::common::position::{Neighbour, Position};
+use crate::common::Direction;
+use crate::common::Direction::*;
+use crate::ghost_corners::GhostCorners;
+use crate::ghost_house::{CurrentHouse, GhostHouse, GhostHouses};
+use crate::ghost_house_gate::{GhostHouseGate, GhostHouseGates};
+use crate::ghosts::brain::{AimHint, ArcadeAccuracy, Brain, BrainContext};
+use crate::ghosts::elroy::Elroy;
+use crate::ghosts::state::State::*;
+use crate::ghosts::state::{State, StateSetter};
+use crate::ghosts::target::controlled::buffer_input_of_player_controlled_ghosts;
+use crate::ghosts::{Ghost, GhostId};
+use crate::ghosts::Ghost::*;
+use crate::life_cycle::LifeCycle::*;
+use crate::map::board::Board;
+use crate::map::navigation::NavigationGraph;
+use crate::players::input::InputBuffer;
+use crate::random::Random;
+
+mod controlled;
+mod eaten;
+mod spawned;
+
+pub struct TargetPlugin;
+
+impl Plugin for TargetPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ArcadeAccuracy::from_args())
+            .init_resource::<DirectionPriority>()
+            .add_system_set(
+                SystemSet::on_update(Running)
+                    .with_system(buffer_input_of_player_controlled_ghosts.before(LTargetSetter)),
+            )
+            .add_system_set(
+                SystemSet::on_update(Running)
+                    .with_system(set_target)
+                    .label(LTargetSetter)
+                    .after(StateSetter),
+            )
+            .add_system_set(
+                SystemSet::on_update(GhostEatenPause)
+                    .with_system(set_target_on_ghost_pause)
+                    .label(LTargetSetter)
+                    .after(StateSetter),
+            );
+    }
+}
+
+/// Marks every system that sets a ghosts target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct LTargetSetter;
+
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+pub struct TargetComponents<'a> {
+    ghost: &'a Ghost,
+    id: &'a GhostId,
+    brain: &'a Brain,
+    target: &'a mut Target,
+    direction: &'a mut Direction,
+    transform: &'a Transform,
+    state: &'a State,
+    input_buffer: Option<&'a mut InputBuffer>,
+    elroy: Option<&'a Elroy>,
+    house: &'a CurrentHouse,
+}
+
+/// Everything the target setters read besides the ghosts themselves.
+#[derive(SystemParam)]
+struct TargetParams<'w, 's> {
+    board: Res<'w, Board>,
+    dimensions: Res<'w, BoardDimensions>,
+    navigation_graph: Res<'w, NavigationGraph>,
+    random: ResMut<'w, Random>,
+    ghost_corners: Res<'w, GhostCorners>,
+    ghost_houses: Res<'w, GhostHouses>,
+    ghost_house_gates: Res<'w, GhostHouseGates>,
+    arcade_accuracy: Res<'w, ArcadeAccuracy>,
+    direction_priority: Res<'w, DirectionPriority>,
+    capman_query:
+        Query<'w, 's, (&'static Transform, &'static Direction), (With<Capman>, Without<Dying>)>,
+}
+
+fn set_target(mut params: TargetParams, mut ghost_query: Query<TargetComponents, Without<Capman>>) {
+    set_targets(&mut params, &mut ghost_query, |setter, capman_found| {
+        let state = *setter.components.state;
+        let player_controlled = setter.components.input_buffer.is_some();
+        let elroy_active = setter.components.elroy.map(Elroy::is_active).unwrap_or(false);
+
+        if player_controlled && matches!(state, Chase | Scatter | Frightened) {
+            setter.set_player_controlled_target();
+            return;
+        }
+
+        // Without a capman left to chase, chasing ghosts wait until one respawns. Every other state goes on.
+        let chasing = state == Chase || (state == Scatter && elroy_active);
+        if chasing && !capman_found {
+            return;
+        }
+
+        match state {
+            Chase => setter.set_chase_target(),
+            Scatter if elroy_active => setter.set_chase_target(),
+            Scatter => setter.set_scatter_target(),
+            Frightened => setter.set_frightened_target(),
+            Eaten => setter.set_eaten_target(),
+            Spawned => setter.set_spawned_target(),
+        }
+    })
+}
+
+/// Set the target when on ghost pause (meaning only eaten and spawned)
+fn set_target_on_ghost_pause(
+    mut params: TargetParams,
+    mut ghost_query: Query<TargetComponents, Without<Capman>>,
+) {
+    set_targets(&mut params, &mut ghost_query, |setter, _| {
+        match *setter.components.state {
+            Eaten => setter.set_eaten_target(),
+            Spawned => setter.set_spawned_target(),
+            _ => (),
+        }
+    })
+}
+
+/// Create a target setter for every ghost without target and let the given function set the target with it. The
+/// function also gets told if a capman was found to chase.
+fn set_targets(
+    params: &mut TargetParams,
+    ghost_query: &mut Query<TargetComponents, Without<Capman>>,
+    set_target: impl Fn(&mut TargetSetter, bool),
+) {
+    let blinky_transforms = get_blinky_transforms(ghost_query);
+
+    for mut components in ghost_query.iter_mut() {
+        if components.target.is_set() {
+            continue;
+        }
+
+        let blinky_transform = get_nearest_blinky_transform(&blinky_transforms, components.transform);
+
+        // Without a capman, the ghost passes himself to the brain, like for a missing blinky
+        let capman = get_nearest_capman(&params.capman_query, components.transform).map(|(tf, dir)| (*tf, *dir));
+        let (pm_transform, pm_dir) = capman.unwrap_or((*components.transform, *components.direction));
+
+        let mut setter = TargetSetter::new(params, pm_transform, pm_dir, blinky_transform, &mut components);
+        set_target(&mut setter, capman.is_some());
+    }
+}
+
+struct TargetSetter<'a, 'b, 'c> {
+    board: &'a Board,
+    dimensions: &'a BoardDimensions,
+    navigation_graph: &'a NavigationGraph,
+    random: &'a mut Random,
+    ghost_corners: &'a GhostCorners,
+    ghost_house: &'a GhostHouse,
+    ghost_house_gate: &'a GhostHouseGate,
+    arcade_accuracy: ArcadeAccuracy,
+    direction_priority: &'a DirectionPriority,
+    capman_transform: Transform,
+    capman_direction: Direction,
+    blinky_transform: Transform,
+    components: &'a mut TargetComponentsItem<'b, 'c>,
+}
+
+impl<'a, 'b, 'c> TargetSetter<'a, 'b, 'c> {
+    fn new(
+        params: &'a mut TargetParams,
+        capman_transform: Transform,
+        capman_direction: Direction,
+        blinky_transform: Transform,
+        components: &'a mut TargetComponentsItem<'b, 'c>,
+    ) -> Self {
+        Self {
+            board: &params.board,
+            dimensions: &params.dimensions,
+            navigation_graph: &params.navigation_graph,
+            random: &mut params.random,
+            ghost_corners: &params.ghost_corners,
+            ghost_house: params.ghost_houses.get(components.house),
+            ghost_house_gate: params.ghost_house_gates.gate_of(components.id),
+            arcade_accuracy: *params.arcade_accuracy,
+            direction_priority: &params.direction_priority,
+            capman_transform,
+            capman_direction,
+            blinky_transform,
+            components,
+        }
+    }
+
+    /// Move to the neighbour nearest to the chase target the ghosts brain chose.
+    fn set_chase_target(&mut self) {
+        let context = self.brain_context();
+        let target = self.components.brain.chase_target(&context);
+        let hints = self.components.brain.chase_hints(&context);
+        let next_target_neighbour = self.get_nearest_neighbour_outside_red_zone_to(target);
+        self.set_target_to_neighbour_on_the_way_to(next_target_neighbour, target, hints)
+    }
+
+    fn set_scatter_target(&mut self) {
+        let target = self.components.brain.scatter_target(&self.brain_context());
+        let next_target_neighbour = self.get_nearest_neighbour_outside_red_zone_to(target);
+        self.set_target_to_neighbour_on_the_way_to(next_target_neighbour, target, vec![])
+    }
+
+    fn set_frightened_target(&mut self) {
+        let possible_neighbours = self.get_possible_neighbours();
+        let next_target_neighbour = match possible_neighbours.len() {
+            0 => self.neighbour_behind(),
+            1 => possible_neighbours[0],
+            _ => {
+                let brain = self.components.brain;
+                brain.frightened_choice(&self.brain_context(), &possible_neighbours, self.random)
+            }
+        };
+        self.set_target_to_neighbour(next_target_neighbour)
+    }
+
+    fn brain_context(&self) -> BrainContext<'a> {
+        BrainContext {
+            dimensions: self.dimensions,
+            navigation_graph: self.navigation_graph,
+            ghost_coordinates: self.components.transform.translation,
+            corner: self.ghost_corners.get_corner(self.components.id),
+            capman_position: self.dimensions.trans_to_pos(&self.capman_transform),
+            capman_direction: self.capman_direction,
+            blinky_position: self.dimensions.trans_to_pos(&self.blinky_transform),
+            arcade_accuracy: self.arcade_accuracy,
+        }
+    }
+
+    /// Get the neighbour with the shortest distance (euclidean) to a given position. If several neighbours are
+    /// equally near, the direction priority decides.
+    ///
+    /// It is generally not allowed for ghosts to turn around, so the position behind the ghost is always filtered. However,
+    /// if due to some circumstances (like bad map design) a ghost has no other way to go, we allow the pour soul to
+    /// turn around.
+    fn get_nearest_neighbour_to(&self, target: Position) -> Neighbour {
+        self.get_nearest_allowed_neighbour_to(target, |_| true)
+    }
+
+    /// Like [TargetSetter::get_nearest_neighbour_to], but the ghost is not allowed to turn up while in a red zone.
+    ///
+    /// This rule only applies in chase and scatter.
+    fn get_nearest_neighbour_outside_red_zone_to(&self, target: Position) -> Neighbour {
+        let in_red_zone = self
+            .board
+            .position_is_red_zone(&self.dimensions.trans_to_pos(self.components.transform));
+        self.get_nearest_allowed_neighbour_to(target, |n| !(in_red_zone && n.direction == Up))
+    }
+
+    fn get_nearest_allowed_neighbour_to(
+        &self,
+        target: Position,
+        allowed: impl Fn(&Neighbour) -> bool,
+    ) -> Neighbour {
+        let allowed_neighbours = self.get_possible_neighbours().into_iter().filter(allowed);
+        let (brain, context) = (self.components.brain, self.brain_context());
+        let distance = |from: &Position, to: &Position| brain.distance(&context, from, to);
+
+        nearest_neighbour_to(&target, allowed_neighbours, self.direction_priority, distance)
+            .unwrap_or_else(|| self.neighbour_behind())
+    }
+
+    /// Return all neighbours the ghost is allowed to go to. Ghosts cannot go through walls and never turn around.
+    fn get_possible_neighbours(&self) -> Vec<Neighbour> {
+        possible_neighbours(
+            self.board,
+            &self.dimensions.trans_to_pos(self.components.transform),
+            &self.components.direction,
+        )
+    }
+
+    fn neighbour_behind(&self) -> Neighbour {
+        self.dimensions
+            .trans_to_pos(self.components.transform)
+            .neighbour_behind(&self.components.direction)
+    }
+
+    fn set_target_to_neighbour(&mut self, neighbour: Neighbour) {
+        *self.components.direction = neighbour.direction;
+        self.components
+            .target
+            .set(self.dimensions.pos_to_vec(&neighbour.position, 0.0));
+    }
+
+    /// Like [TargetSetter::set_target_to_neighbour], but remembers the position the brain aimed at and how.
+    fn set_target_to_neighbour_on_the_way_to(
+        &mut self,
+        neighbour: Neighbour,
+        aim: Position,
+        hints: Vec<AimHint>,
+    ) {
+        *self.components.direction = neighbour.direction;
+        self.components.target.set_aimed(
+            self.dimensions.pos_to_vec(&neighbour.position, 0.0),
+            aim,
+            hints,
+        );
+    }
+}
+
+/// Get the transform and direction of the capman nearest to the given ghost transform.
+///
+/// Ghosts always hunt the nearest capman which is still playing. If no capman is left, None is returned.
+fn get_nearest_capman<'a>(
+    capman_query: &'a Query<(&Transform, &Direction), (With<Capman>, Without<Dying>)>,
+    ghost_transform: &Transform,
+) -> Option<(&'a Transform, &'a Direction)> {
+    capman_query.iter().min_by(|(tf_a, _), (tf_b, _)| {
+        let distance_a = tf_a.translation.distance(ghost_transform.translation);
+        let distance_b = tf_b.translation.distance(ghost_transform.translation);
+        distance_a.total_cmp(&distance_b)
+    })
+}
+
+/// Get the transforms of every blinky.
+fn get_blinky_transforms(query: &Query<TargetComponents, Without<Capman>>) -> Vec<Transform> {
+    query
+        .iter()
+        .filter(|comps| comps.ghost == &Blinky)
+        .map(|comps| *comps.transform)
+        .collect()
+}
+
+/// Ghosts that need blinky (like inky) use the nearest one. If the map has no blinky, they use themselves.
+fn get_nearest_blinky_transform(blinky_transforms: &[Transform], ghost_transform: &Transform) -> Transform {
+    blinky_transforms
+        .iter()
+        .min_by(|tf_a, tf_b| {
+            let distance_a = tf_a.translation.distance(ghost_transform.translation);
+            let distance_b = tf_b.translation.distance(ghost_transform.translation);
+            distance_a.total_cmp(&distance_b)
+        })
+        .copied()
+        .unwrap_or(*ghost_transform)
+}
+
+/// Return all neighbours of the position a ghost moving in the given direction is allowed to go to.
+/// Ghosts cannot go through walls and never turn around.
+fn possible_neighbours(board: &Board, position: &Position, direction: &Direction) -> Vec<Neighbour> {
+    position
+        .get_neighbours()
+        .into_iter()
+        .filter(|n| n.direction != direction.opposite())
+        .filter(|n| !board.position_is_wall_or_entrance(&n.position))
+        .collect()
+}
+
+/// Return the neighbour nearest to the target, measured with the given distance function.
+/// Ties are resolved by the given direction priority.
+fn nearest_neighbour_to(
+    target: &Position,
+    neighbours: impl IntoIterator<Item = Neighbour>,
+    direction_priority: &DirectionPriority,
+    distance: impl Fn(&Position, &Position) -> isize,
+) -> Option<Neighbour> {
+    neighbours.into_iter().min_by_key(|n| {
+        (
+            distance(&n.position, target),
+            direction_priority.rank_of(&n.direction),
+        )
+    })
+}
+
+/// The order in which ghosts prefer directions when several neighbours are equally near to their target.
+///
+/// The most preferred direction comes first. The default is the order of the arcade: up, left, down, right.
+#[derive(Clone, Debug)]
+pub struct DirectionPriority(pub [Direction; 4]);
+
+impl DirectionPriority {
+    /// Directions missing in the priority are the least preferred.
+    fn rank_of(&self, direction: &Direction) -> usize {
+        self.0
+            .iter()
+            .position(|dir| dir == direction)
+            .unwrap_or(self.0.len())
+    }
+}
+
+impl Default for DirectionPriority {
+    fn default() -> Self {
+        DirectionPriority([Up, Left, Down, Right])
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Target {
+    coordinates: Option<Vec3>,
+    aim: Option<Position>,
+    hints: Vec<AimHint>,
+}
+
+impl Target {
+    pub fn new() -> Self {
+        Target {
+            coordinates: None,
+            aim: None,
+            hints: vec![],
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.coordinates.is_some()
+    }
+
+    pub fn is_not_set(&self) -> bool {
+        !self.is_set()
+    }
+
+    /// Return the coordinates without checking if they are present.
+    /// The check should happen somewhere else anyway.
+    pub fn get(&self) -> Vec3 {
+        self.coordinates.unwrap()
+    }
+
+    /// The position the brain aimed at when the last target was chosen in chase or scatter. The target itself is
+    /// only the next field on the way there. The aim stays after the target was reached, until a new one is set.
+    pub fn aim(&self) -> Option<Position> {
+        self.aim
+    }
+
+    /// The shapes the brain used to explain the aim (see GhostBrain::chase_hints).
+    pub fn hints(&self) -> &[AimHint] {
+        &self.hints
+    }
+
+    pub fn set(&mut self, coordinates: Vec3) {
+        self.coordinates = Some(coordinates);
+        self.aim = None;
+        self.hints.clear()
+    }
+
+    pub fn set_aimed(&mut self, coordinates: Vec3, aim: Position, hints: Vec<AimHint>) {
+        self.coordinates = Some(coordinates);
+        self.aim = Some(aim);
+        self.hints = hints
+    }
+
+    pub fn clear(&mut self) {
+        self.coordinates = None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::board_dimensions::BoardDimensions;
+    use crate::common::position::Position;
+    use crate::common::Direction;
+    use crate::common::Direction::*;
+    use crate::ghost_corners::GhostCorners;
+    use crate::ghost_house::{CurrentHouse, GhostHouses};
+    use crate::ghost_house_gate::GhostHouseGates;
+    use crate::ghosts::brain::{ArcadeAccuracy, BrainContext, BrainKind, GhostBrain, GhostBrains};
+    use crate::ghosts::state::State;
+    use crate::ghosts::target::{
+        nearest_neighbour_to, possible_neighbours, set_target, DirectionPriority, Target,
+    };
+    use crate::ghosts::Ghost::Clyde;
+    use crate::ghosts::GhostId;
+    use crate::map::board::Board;
+    use crate::map::navigation::NavigationGraph;
+    use crate::map::Map;
+    use crate::random::Random;
+    use crate::specs_per_level::Spec;
+
+    /// A crossing on the default map where every direction is free.
+    const CROSSING: Position = Position { x: 8, y: 25 };
+
+    fn next_direction(
+        moving: Direction,
+        target: Position,
+        direction_priority: &DirectionPriority,
+    ) -> Direction {
+        let board = Board::new(&Map::load_default());
+        let neighbours = possible_neighbours(&board, &CROSSING, &moving);
+        assert_eq!(neighbours.len(), 3, "the crossing should have three ways besides turning around");
+
+        nearest_neighbour_to(&target, neighbours, direction_priority, Position::distance_to)
+            .unwrap()
+            .direction
+    }
+
+    #[test]
+    fn up_is_preferred_over_left() {
+        let target = Position::new(7, 26);
+        assert_eq!(next_direction(Left, target, &DirectionPriority::default()), Up)
+    }
+
+    #[test]
+    fn up_is_preferred_over_right() {
+        let target = Position::new(9, 26);
+        assert_eq!(next_direction(Right, target, &DirectionPriority::default()), Up)
+    }
+
+    #[test]
+    fn left_is_preferred_over_down() {
+        let target = Position::new(7, 24);
+        assert_eq!(next_direction(Left, target, &DirectionPriority::default()), Left)
+    }
+
+    #[test]
+    fn down_is_preferred_over_right() {
+        let target = Position::new(9, 24);
+        assert_eq!(next_direction(Right, target, &DirectionPriority::default()), Down)
+    }
+
+    #[test]
+    fn the_nearer_neighbour_wins_regardless_of_the_priority() {
+        let target = Position::new(12, 25);
+        assert_eq!(next_direction(Right, target, &DirectionPriority::default()), Right)
+    }
+
+    #[test]
+    fn the_priority_can_be_changed() {
+        let priority = DirectionPriority([Right, Down, Left, Up]);
+
+        assert_eq!(next_direction(Right, Position::new(9, 24), &priority), Right);
+        assert_eq!(next_direction(Left, Position::new(7, 26), &priority), Left);
+    }
+
+    /// Scatters to the middle of the maze instead of his corner.
+    struct HomesickBrain;
+
+    const HOME: Position = Position { x: 13, y: 17 };
+
+    impl GhostBrain for HomesickBrain {
+        fn chase_target(&self, context: &BrainContext) -> Position {
+            context.capman_position
+        }
+
+        fn scatter_target(&self, _context: &BrainContext) -> Position {
+            HOME
+        }
+    }
+
+    #[test]
+    fn a_ghost_follows_the_brain_he_spawned_with() {
+        let map = Map::load_default();
+        let board = Board::new(&map);
+        let dimensions = BoardDimensions::new(&board);
+        let coordinates = dimensions.pos_to_vec(&CROSSING, 0.0);
+        let mut brains = GhostBrains::default();
+        brains.register("homesick", || HomesickBrain);
+
+        let mut app = App::new();
+        app.insert_resource(NavigationGraph::new(&map, &board))
+            .insert_resource(GhostHouses::new(&map, &dimensions))
+            .insert_resource(GhostHouseGates::new(&map, &Spec::default()))
+            .insert_resource(GhostCorners::new(&map))
+            .insert_resource(Random::from_seed(0))
+            .insert_resource(ArcadeAccuracy::default())
+            .insert_resource(DirectionPriority::default())
+            .insert_resource(dimensions)
+            .insert_resource(board)
+            .add_system(set_target);
+        let ghost = app
+            .world
+            .spawn()
+            .insert_bundle((Clyde, GhostId(3), CurrentHouse(0), State::Scatter, Target::new(), Left))
+            .insert(brains.create("homesick", BrainKind::Classic))
+            .insert(Transform::from_translation(coordinates))
+            .id();
+
+        app.update();
+
+        assert_eq!(app.world.get::<Target>(ghost).unwrap().aim(), Some(HOME));
+    }
+}
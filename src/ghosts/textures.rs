@@ -6,17 +6,25 @@ use crate::game_assets::loaded_assets::LoadedAssets;
 use crate::ghosts::Ghost;
 use crate::ghosts::Ghost::*;
 use crate::ghosts::state::State;
+use crate::level::Level;
+use crate::specs_per_level::SpecsPerLevel;
 use crate::sprite_sheet::SpriteSheet;
 
+/// The time one flash of a frightened ghost takes.
+const FLASH_SECONDS: f32 = 0.5;
+
 pub(in crate::ghosts) fn update_ghost_appearance(
     energizer_timer: Option<Res<EnergizerTimer>>,
+    level: Res<Level>,
+    specs_per_level: Res<SpecsPerLevel>,
     mut query: Query<(&Direction, &State, &mut Animations)>,
 ) {
+    let frightened_flashes = specs_per_level.get_for(&level).frightened_flashes;
+
     for (direction, state, mut animations) in query.iter_mut() {
         match state {
             State::Frightened => match energizer_timer {
-                // animate a frightened ghost differently if the energizer timer is almost ending
-                Some(ref timer) if timer.remaining() < 2.0 => animations.change_animation_to("frightened_blinking"),
+                Some(ref timer) if is_flashing(timer.remaining(), frightened_flashes) => animations.change_animation_to("frightened_blinking"),
                 _ => animations.change_animation_to("frightened"),
             },
             State::Eaten => {
@@ -27,6 +35,11 @@ pub(in crate::ghosts) fn update_ghost_appearance(
     }
 }
 
+/// Animate a frightened ghost differently if the energizer timer is almost ending, so it flashes the given number of times.
+fn is_flashing(remaining_seconds: f32, frightened_flashes: usize) -> bool {
+    remaining_seconds < frightened_flashes as f32 * FLASH_SECONDS
+}
+
 pub(in crate::ghosts) fn create_animations_for_ghost(ghost: &Ghost, game_assets: &LoadedAssets, sprite_sheets: &Assets<SpriteSheet>) -> Animations {
     match *ghost {
         Blinky => create_animations_for(game_assets, sprite_sheets, ["textures/ghost/blinky_up", "textures/ghost/blinky_down", "textures/ghost/blinky_left", "textures/ghost/blinky_right"]),
@@ -75,7 +88,7 @@ fn create_frightened_animation(sprite_sheet: &SpriteSheet) -> Animation {
 
 fn create_frightened_blinking_animation(sprite_sheet: &SpriteSheet) -> Animation {
     Animation::from_textures(
-        0.5,
+        FLASH_SECONDS,
         true,
         sprite_sheet.images_at(0..4)
     )
@@ -88,4 +101,24 @@ pub(in crate::ghosts) fn start_animation(
     for mut anim in &mut query {
         anim.resume()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ghosts::textures::{is_flashing, FLASH_SECONDS};
+
+    #[test]
+    fn frightened_ghosts_flash_during_the_last_flashes_of_the_energizer() {
+        let flashes = 5;
+        let flashing_seconds = flashes as f32 * FLASH_SECONDS;
+
+        assert!(!is_flashing(flashing_seconds + 0.01, flashes));
+        assert!(is_flashing(flashing_seconds - 0.01, flashes));
+        assert!(is_flashing(0.01, flashes));
+    }
+
+    #[test]
+    fn frightened_ghosts_do_not_flash_on_levels_without_flashes() {
+        assert!(!is_flashing(0.01, 0));
+    }
+}
@@ -0,0 +1,213 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::Difficulty;
+use crate::level::Level;
+use crate::life_cycle::LifeCycle::GameOver;
+use crate::players::GameMode;
+use crate::score::Score;
+
+/// The file the high scores are stored in, relative to the working directory.
+const HIGH_SCORES_FILE: &str = "high_scores.json";
+
+/// Only this many high scores are kept.
+const MAX_HIGH_SCORES: usize = 10;
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameOver).with_system(save_high_scores));
+    }
+}
+
+/// A finished game worth remembering.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct HighScore {
+    score: usize,
+    level: usize,
+    difficulty: Difficulty,
+    game_mode: GameMode,
+}
+
+/// The best scores of all games played, ordered from best to worst.
+#[derive(Default, Serialize, Deserialize)]
+struct HighScores(Vec<HighScore>);
+
+impl HighScores {
+    /// Load the high scores from the given file.
+    ///
+    /// A file that cannot be read is moved to '<file>.bak', so saving does not overwrite the scores in it. If
+    /// even that fails, None is returned and nothing may be saved over the file.
+    fn load(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref();
+        let error = match Self::read(path) {
+            Ok(high_scores) => return Some(high_scores),
+            Err(e) => e,
+        };
+        error!(
+            "Could not read the high scores from {}: {}",
+            path.display(),
+            error
+        );
+
+        let backup = backup_path(path);
+        match fs::rename(path, &backup) {
+            Ok(()) => {
+                warn!(
+                    "Moved the high scores to {}, starting without high scores",
+                    backup.display()
+                );
+                Some(HighScores::default())
+            }
+            Err(e) => {
+                error!(
+                    "Could not move the high scores to {}, so they are kept: {}",
+                    backup.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// A missing file means no high scores yet.
+    fn read(path: &Path) -> anyhow::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(HighScores::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn add(&mut self, high_score: HighScore) {
+        self.0.push(high_score);
+        self.0.sort_by_key(|h| std::cmp::Reverse(h.score));
+        self.0.truncate(MAX_HIGH_SCORES);
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    backup.into()
+}
+
+/// Every capman player gets their own entry, together with the difficulty and mode they played.
+fn save_high_scores(
+    score: Res<Score>,
+    level: Res<Level>,
+    difficulty: Res<Difficulty>,
+    game_mode: Res<GameMode>,
+) {
+    let mut high_scores = match HighScores::load(HIGH_SCORES_FILE) {
+        Some(high_scores) => high_scores,
+        None => return,
+    };
+
+    for player in game_mode.capman_players() {
+        high_scores.add(HighScore {
+            score: score.of(&player),
+            level: **level,
+            difficulty: *difficulty,
+            game_mode: *game_mode,
+        })
+    }
+
+    if let Err(e) = high_scores.save(HIGH_SCORES_FILE) {
+        error!("Could not save the high scores: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use crate::difficulty::Difficulty;
+    use crate::high_scores::{backup_path, HighScore, HighScores, MAX_HIGH_SCORES};
+    use crate::players::GameMode;
+
+    fn high_score(score: usize) -> HighScore {
+        HighScore {
+            score,
+            level: 1,
+            difficulty: Difficulty::Arcade,
+            game_mode: GameMode::SinglePlayer,
+        }
+    }
+
+    #[test]
+    fn high_scores_are_ordered_from_best_to_worst() {
+        let mut high_scores = HighScores::default();
+
+        high_scores.add(high_score(100));
+        high_scores.add(high_score(300));
+        high_scores.add(high_score(200));
+
+        let scores = high_scores.0.iter().map(|h| h.score).collect::<Vec<_>>();
+        assert_eq!(scores, vec![300, 200, 100])
+    }
+
+    #[test]
+    fn only_the_best_high_scores_are_kept() {
+        let mut high_scores = HighScores::default();
+
+        for score in 0..=MAX_HIGH_SCORES {
+            high_scores.add(high_score(score));
+        }
+
+        assert_eq!(high_scores.0.len(), MAX_HIGH_SCORES);
+        assert!(high_scores.0.iter().all(|h| h.score > 0))
+    }
+
+    #[test]
+    fn the_difficulty_is_stored_with_the_high_score() {
+        let mut high_scores = HighScores::default();
+        high_scores.add(HighScore {
+            difficulty: Difficulty::Nightmare,
+            ..high_score(100)
+        });
+
+        let json = serde_json::to_string(&high_scores).unwrap();
+        let loaded = serde_json::from_str::<HighScores>(&json).unwrap();
+
+        assert_eq!(loaded.0[0].difficulty, Difficulty::Nightmare)
+    }
+
+    #[test]
+    fn a_missing_file_means_no_high_scores_yet() {
+        let file = temp_file("missing");
+
+        assert_eq!(HighScores::load(&file).map(|h| h.0), Some(vec![]));
+        assert!(!backup_path(&file).exists());
+    }
+
+    #[test]
+    fn an_unreadable_file_is_backed_up_instead_of_overwritten() {
+        let file = temp_file("broken");
+        fs::write(&file, "[{\"score\": 1").unwrap();
+
+        let loaded = HighScores::load(&file);
+        let backup = fs::read_to_string(backup_path(&file));
+        fs::remove_file(backup_path(&file)).unwrap();
+
+        assert_eq!(loaded.map(|h| h.0), Some(vec![]));
+        assert!(!file.exists());
+        assert_eq!(backup.unwrap(), "[{\"score\": 1");
+    }
+
+    fn temp_file(name: &str) -> PathBuf {
+        let file_name = format!("capman_high_scores_{}_{}.json", name, std::process::id());
+        std::env::temp_dir().join(file_name)
+    }
+}
@@ -1,7 +1,7 @@
 use crate::board_dimensions::BoardDimensions;
 use bevy::prelude::*;
 
-use crate::capman::Capman;
+use crate::capman::{Capman, Dying, LCapmanMovement};
 use crate::edibles::dots::{Dot, EatenDots};
 use crate::edibles::energizer::Energizer;
 use crate::edibles::fruit::{Fruit, FruitDespawnTimer};
@@ -13,7 +13,8 @@ pub struct InteractionsPlugin;
 
 impl Plugin for InteractionsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<ECapmanHit>()
+        app.init_resource::<GodMode>()
+            .add_event::<ECapmanHit>()
             .add_event::<EGhostEaten>()
             .add_event::<EDotEaten>()
             .add_event::<EEnergizerEaten>()
@@ -24,7 +25,9 @@ impl Plugin for InteractionsPlugin {
                     .with_system(capman_eat_dot)
                     .with_system(capman_eat_energizer)
                     .with_system(eat_fruit_when_capman_touches_it)
-                    .label(LCapmanEnergizerHitDetection),
+                    .label(LCapmanEnergizerHitDetection)
+                    .label(LCapmanInteractions)
+                    .after(LCapmanMovement),
             );
     }
 }
@@ -36,42 +39,58 @@ pub struct LCapmanGhostHitDetection;
 #[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
 pub struct LCapmanEnergizerHitDetection;
 
+/// Marks every system that checks what capman touched. Systems reading their events run after them, so every
+/// event is handled in the frame it was fired (networked games depend on this order being the same everywhere).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub struct LCapmanInteractions;
+
+/// If true, capman is not hit by ghosts in chase or scatter. He can still eat frightened ghosts.
+#[derive(Debug, Default, Deref, DerefMut)]
+pub struct GodMode(pub bool);
+
 /// Fired when capman was hit by a ghost.
-pub struct ECapmanHit;
+/// Contains the hit capman entity.
+#[derive(Copy, Clone, Deref)]
+pub struct ECapmanHit(pub Entity);
 
 /// Fired when Capman ate a ghost in frightened state.
-/// Contains the eaten ghost entity and transform.
+/// Contains the eaten ghost entity and transform and the capman entity that ate the ghost.
 #[derive(Copy, Clone)]
-pub struct EGhostEaten(pub Entity, pub Transform);
+pub struct EGhostEaten(pub Entity, pub Transform, pub Entity);
 
 /// Fired when capman eats a dot.
-pub struct EDotEaten;
+/// Contains the capman entity that ate the dot.
+#[derive(Copy, Clone, Deref)]
+pub struct EDotEaten(pub Entity);
 
 /// Fired when capman eats an energizer.
-#[derive(Copy, Clone)]
-pub struct EEnergizerEaten;
+/// Contains the capman entity that ate the energizer.
+#[derive(Copy, Clone, Deref)]
+pub struct EEnergizerEaten(pub Entity);
 
 /// Event that gets fired when capman ate a fruit.
-pub struct EFruitEaten(pub Fruit, pub Transform);
+/// Contains the fruit, its transform and the capman entity that ate it.
+pub struct EFruitEaten(pub Fruit, pub Transform, pub Entity);
 
 fn capman_hits_ghost(
     mut commands: Commands,
     mut killed_event_writer: EventWriter<ECapmanHit>,
     mut eat_event_writer: EventWriter<EGhostEaten>,
     dimensions: Res<BoardDimensions>,
-    capman_query: Query<&Transform, With<Capman>>,
+    god_mode: Res<GodMode>,
+    capman_query: Query<(Entity, &Transform), (With<Capman>, Without<Dying>)>,
     ghost_query: Query<(Entity, &Transform, &State), With<Ghost>>,
 ) {
-    for capman_transform in &capman_query {
+    for (capman_entity, capman_transform) in &capman_query {
         for (entity, ghost_transform, state) in &ghost_query {
             if dimensions.trans_to_pos(capman_transform) == dimensions.trans_to_pos(ghost_transform)
             {
-                if let State::Scatter | State::Chase = state {
-                    killed_event_writer.send(ECapmanHit)
+                if let (State::Scatter | State::Chase, false) = (state, **god_mode) {
+                    killed_event_writer.send(ECapmanHit(capman_entity))
                 }
 
                 if let State::Frightened = state {
-                    eat_event_writer.send(EGhostEaten(entity, *ghost_transform));
+                    eat_event_writer.send(EGhostEaten(entity, *ghost_transform, capman_entity));
                     commands.insert_resource(CurrentlyEatenGhost(entity))
                 }
             }
@@ -84,15 +103,15 @@ fn capman_eat_dot(
     mut event_writer: EventWriter<EDotEaten>,
     mut eaten_dots: ResMut<EatenDots>,
     dimensions: Res<BoardDimensions>,
-    capman_positions: Query<&Transform, With<Capman>>,
+    capman_positions: Query<(Entity, &Transform), (With<Capman>, Without<Dying>)>,
     dot_positions: Query<(Entity, &Transform), With<Dot>>,
 ) {
-    for capman_tf in &capman_positions {
+    for (capman, capman_tf) in &capman_positions {
         for (entity, dot_tf) in &dot_positions {
             if dimensions.trans_to_pos(capman_tf) == dimensions.trans_to_pos(dot_tf) {
                 commands.entity(entity).despawn();
                 eaten_dots.increment();
-                event_writer.send(EDotEaten)
+                event_writer.send(EDotEaten(capman))
             }
         }
     }
@@ -102,16 +121,16 @@ fn capman_eat_energizer(
     mut commands: Commands,
     mut event_writer: EventWriter<EEnergizerEaten>,
     dimensions: Res<BoardDimensions>,
-    capman_positions: Query<&Transform, With<Capman>>,
+    capman_positions: Query<(Entity, &Transform), (With<Capman>, Without<Dying>)>,
     energizer_positions: Query<(Entity, &Transform), With<Energizer>>,
 ) {
-    for capman_transform in &capman_positions {
+    for (capman, capman_transform) in &capman_positions {
         for (energizer_entity, energizer_transform) in &energizer_positions {
             if dimensions.trans_to_pos(energizer_transform)
                 == dimensions.trans_to_pos(capman_transform)
             {
                 commands.entity(energizer_entity).despawn();
-                event_writer.send(EEnergizerEaten)
+                event_writer.send(EEnergizerEaten(capman))
             }
         }
     }
@@ -122,15 +141,15 @@ fn eat_fruit_when_capman_touches_it(
     mut commands: Commands,
     mut event_writer: EventWriter<EFruitEaten>,
     dimensions: Res<BoardDimensions>,
-    capman_query: Query<&Transform, With<Capman>>,
+    capman_query: Query<(Entity, &Transform), (With<Capman>, Without<Dying>)>,
     fruit_query: Query<(Entity, &Fruit, &Transform)>,
 ) {
-    for capman_tf in &capman_query {
+    for (capman, capman_tf) in &capman_query {
         for (entity, fruit, fruit_tf) in &fruit_query {
             if dimensions.trans_to_pos(capman_tf) == dimensions.trans_to_pos(fruit_tf) {
                 commands.entity(entity).despawn();
                 commands.remove_resource::<FruitDespawnTimer>();
-                event_writer.send(EFruitEaten(*fruit, *fruit_tf))
+                event_writer.send(EFruitEaten(*fruit, *fruit_tf, capman))
             }
         }
     }
@@ -14,7 +14,7 @@ impl Plugin for LevelPlugin {
     }
 }
 
-#[derive(Deref, DerefMut, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[derive(Debug, Deref, DerefMut, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct Level(pub usize);
 
 impl Level {
@@ -5,21 +5,28 @@ mod background_noise;
 mod board_dimensions;
 mod camera;
 mod capman;
+mod clock;
 pub mod common;
 mod constants;
 mod debug;
+mod difficulty;
+mod director;
 mod edibles;
+mod game;
 mod game_assets;
 mod game_over_screen;
 mod ghost_corners;
 mod ghost_house;
 mod ghost_house_gate;
 mod ghosts;
+mod high_scores;
 mod interactions;
 mod level;
 mod life_cycle;
 mod lives;
 pub mod map;
+mod network;
+mod players;
 mod random;
 mod ready_screen;
 mod score;
@@ -1,9 +1,10 @@
-use crate::capman::ECapmanDead;
+use crate::capman::{all_playing_capmen_hit, Capman, Dying, ECapmanDead, LCapmanDeathCheck};
 use crate::edibles::EAllEdiblesEaten;
 use crate::game_assets::EAllAssetsLoaded;
-use crate::interactions::{ECapmanHit, EGhostEaten};
+use crate::interactions::{ECapmanHit, EGhostEaten, LCapmanInteractions};
 use crate::lives::Life;
 use bevy::prelude::*;
+use bevy::utils::HashSet;
 use LifeCycle::*;
 
 /// All lifecycle states of the app. See ./resources/lifecycle.png for a visualization.
@@ -41,14 +42,16 @@ impl Plugin for GameStatePlugin {
                 SystemSet::on_update(Running)
                     .with_system(switch_to_dying_when_capman_was_hit)
                     .with_system(switch_to_level_transition_when_all_edibles_eaten)
-                    .with_system(switch_to_ghost_eaten_pause_when_ghost_was_eaten),
+                    .with_system(switch_to_ghost_eaten_pause_when_ghost_was_eaten)
+                    .after(LCapmanInteractions),
             )
             .add_system_set(SystemSet::on_enter(CapmanHit).with_system(start_state_timer))
             .add_system_set(
                 SystemSet::on_update(CapmanHit).with_system(switch_state_when_state_timer_finished),
             )
             .add_system_set(
-                SystemSet::on_update(CapmanDying).with_system(switch_to_dead_when_capman_is_dead),
+                SystemSet::on_update(CapmanDying)
+                    .with_system(switch_to_dead_when_capman_is_dead.after(LCapmanDeathCheck)),
             )
             .add_system_set(SystemSet::on_enter(CapmanDead).with_system(start_state_timer))
             .add_system_set(
@@ -69,8 +72,8 @@ impl Plugin for GameStatePlugin {
 
 /// Some lifecycle states just wait for a few seconds before switching. This timer and the related systems
 /// handle these states
-#[derive(Deref, DerefMut)]
-struct StateTimer(Timer);
+#[derive(Debug, Deref, DerefMut)]
+pub struct StateTimer(Timer);
 
 fn start_game_when_all_assets_loaded(
     mut life_cycle: ResMut<State<LifeCycle>>,
@@ -119,11 +122,16 @@ fn switch_state_when_state_timer_finished(
     }
 }
 
+/// The game only switches to the dying states if no capman keeps playing. If other capmen are
+/// still alive, the hit capman dies on his own while the game continues.
 fn switch_to_dying_when_capman_was_hit(
     mut event_reader: EventReader<ECapmanHit>,
     mut game_state: ResMut<State<LifeCycle>>,
+    query: Query<Entity, (With<Capman>, Without<Dying>)>,
 ) {
-    for _ in event_reader.iter() {
+    let hit_capmen = event_reader.iter().map(|e| **e).collect::<HashSet<_>>();
+
+    if all_playing_capmen_hit(&hit_capmen, &query) {
         game_state.set(CapmanHit).unwrap()
     }
 }
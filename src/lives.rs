@@ -1,10 +1,12 @@
 use crate::board_dimensions::BoardDimensions;
 use crate::game_assets::loaded_assets::LoadedAssets;
-use crate::interactions::ECapmanHit;
+use crate::interactions::{ECapmanHit, LCapmanInteractions};
 use crate::life_cycle::LifeCycle;
 use crate::life_cycle::LifeCycle::Start;
-use crate::score::Score;
+use crate::players::{GameMode, Player};
+use crate::score::{LScore, Score};
 use bevy::prelude::*;
+use std::collections::BTreeMap;
 
 pub struct LivesPlugin;
 
@@ -14,8 +16,8 @@ impl Plugin for LivesPlugin {
             .add_system_set(SystemSet::on_enter(Start).with_system(spawn_lives))
             .add_system_set(
                 SystemSet::on_update(LifeCycle::Running)
-                    .with_system(remove_life_when_capman_dies)
-                    .with_system(add_life_if_player_reaches_specific_score),
+                    .with_system(remove_life_when_capman_dies.after(LCapmanInteractions))
+                    .with_system(add_life_if_player_reaches_specific_score.after(LScore)),
             );
     }
 }
@@ -23,42 +25,79 @@ impl Plugin for LivesPlugin {
 const LIVES: usize = 3;
 
 /// Represents a life of capman. A life gets removed if capman dies or gets added
-/// if the player reaches a specific score
-#[derive(Component, Ord, PartialOrd, Eq, PartialEq)]
+/// if the player reaches a specific score.
+///
+/// Every life belongs to the player it is spawned with (as a Player component).
+#[derive(Component, Debug, Ord, PartialOrd, Eq, PartialEq)]
 pub struct Life(usize);
 
-/// Keeps track how many points the player needs to get a new life for capman.
-#[derive(Deref, DerefMut)]
-pub struct PointsRequiredForExtraLife(usize);
+/// Keeps track how many points each player needs to get a new life for capman.
+#[derive(Debug)]
+pub struct PointsRequiredForExtraLife(BTreeMap<Player, usize>);
 
 impl PointsRequiredForExtraLife {
     pub fn new() -> Self {
-        PointsRequiredForExtraLife(10000)
+        PointsRequiredForExtraLife(BTreeMap::default())
     }
 
-    pub fn increase_limit(&mut self) {
-        **self += 10000
+    pub fn get(&self, player: &Player) -> usize {
+        *self.0.get(player).unwrap_or(&10000)
+    }
+
+    pub fn increase_limit(&mut self, player: &Player) {
+        let limit = self.get(player) + 10000;
+        self.0.insert(*player, limit);
     }
 }
 
 fn spawn_lives(
     mut commands: Commands,
     asset_handles: Res<LoadedAssets>,
+    game_mode: Res<GameMode>,
     dimensions: Res<BoardDimensions>,
 ) {
-    for i in 0..LIVES {
-        spawn_life(&mut commands, &asset_handles, i, &dimensions)
+    for player in game_mode.capman_players() {
+        for i in 0..LIVES {
+            spawn_life(&mut commands, &asset_handles, player, i, &dimensions)
+        }
     }
 }
 
+/// Spawn or despawn lives of the player until they have exactly the given number of lives.
+pub fn set_lives(
+    commands: &mut Commands,
+    asset_handles: &LoadedAssets,
+    dimensions: &BoardDimensions,
+    player: Player,
+    lives: usize,
+    query: &Query<(Entity, &Life, &Player)>,
+) {
+    let mut remaining = 0;
+
+    for (entity, life, _) in query.iter().filter(|(_, _, p)| **p == player) {
+        if life.0 >= lives {
+            commands.entity(entity).despawn()
+        } else {
+            remaining += 1
+        }
+    }
+
+    for i in remaining..lives {
+        spawn_life(commands, asset_handles, player, i, dimensions)
+    }
+}
+
+/// Every player gets their own row of lives below the board. Player one uses the first row.
 fn spawn_life(
     commands: &mut Commands,
     asset_handles: &LoadedAssets,
+    player: Player,
     life_index: usize,
     dimensions: &BoardDimensions,
 ) {
     let origin = dimensions.origin();
     let life_x = origin.x + (life_index as f32) * (dimensions.life());
+    let row = player as usize + 1;
 
     commands
         .spawn()
@@ -70,23 +109,32 @@ fn spawn_life(
             },
             transform: Transform::from_translation(Vec3::new(
                 life_x,
-                origin.y - dimensions.life(),
+                origin.y - row as f32 * dimensions.life(),
                 0.0,
             )),
             ..default()
         })
-        .insert(Life(life_index));
+        .insert(Life(life_index))
+        .insert(player);
 }
 
 fn remove_life_when_capman_dies(
     mut commands: Commands,
     mut event_reader: EventReader<ECapmanHit>,
-    query: Query<(Entity, &Life)>,
+    capman_query: Query<&Player>,
+    query: Query<(Entity, &Life, &Player)>,
 ) {
-    for _ in event_reader.iter() {
-        let life_to_remove = query.iter().max_by(|(_, i0), (_, i1)| i0.cmp(i1));
+    for event in event_reader.iter() {
+        let hit_player = match capman_query.get(**event) {
+            Ok(player) => player,
+            Err(_) => continue,
+        };
+        let life_to_remove = query
+            .iter()
+            .filter(|(_, _, player)| player == &hit_player)
+            .max_by(|(_, i0, _), (_, i1, _)| i0.cmp(i1));
 
-        if let Some((e, _)) = life_to_remove {
+        if let Some((e, _, _)) = life_to_remove {
             commands.entity(e).despawn()
         }
     }
@@ -95,14 +143,17 @@ fn remove_life_when_capman_dies(
 fn add_life_if_player_reaches_specific_score(
     mut commands: Commands,
     game_assets: Res<LoadedAssets>,
+    game_mode: Res<GameMode>,
     score: Res<Score>,
     mut points_required_for_extra_life: ResMut<PointsRequiredForExtraLife>,
     dimensions: Res<BoardDimensions>,
-    query: Query<&Life>,
+    query: Query<&Player, With<Life>>,
 ) {
-    if **score >= **points_required_for_extra_life {
-        let index = query.iter().count();
-        spawn_life(&mut commands, &game_assets, index, &dimensions);
-        points_required_for_extra_life.increase_limit();
+    for player in game_mode.capman_players() {
+        if score.of(&player) >= points_required_for_extra_life.get(&player) {
+            let index = query.iter().filter(|p| *p == &player).count();
+            spawn_life(&mut commands, &game_assets, player, index, &dimensions);
+            points_required_for_extra_life.increase_limit(&player);
+        }
     }
 }
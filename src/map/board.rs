@@ -1,7 +1,7 @@
-use bevy::utils::HashSet;
+use bevy::utils::{HashMap, HashSet};
 
 use crate::common::position::Position;
-use crate::map::{Element, Map};
+use crate::map::{Element, Map, SpeedZoneAffects};
 use crate::map::Element::*;
 use crate::is;
 
@@ -14,6 +14,8 @@ pub struct Board {
     wall_positions: HashSet<Position>,
     ghost_house_entrance_positions: HashSet<Position>,
     tunnel_positions: HashSet<Position>,
+    red_zone_positions: HashSet<Position>,
+    speed_zones: HashMap<Position, Vec<(SpeedZoneAffects, usize)>>,
     pub width: usize,
     pub height: usize,
 }
@@ -25,11 +27,15 @@ impl Board {
         let wall_positions = Self::positions_matching_filter(&map, is!(Wall {..} | InvisibleWall));
         let ghost_house_entrance_positions = Self::positions_matching_filter(&map, is!(GhostHouseEntrance {..}));
         let tunnel_positions = Self::positions_matching_filter(&map, is!(Tunnel {..} | TunnelEntrance | TunnelHallway));
+        let red_zone_positions = Self::positions_matching_filter(&map, is!(RedZone));
+        let speed_zones = Self::collect_speed_zones(&map);
 
         Board {
             wall_positions,
             ghost_house_entrance_positions,
             tunnel_positions,
+            red_zone_positions,
+            speed_zones,
             width,
             height,
         }
@@ -42,6 +48,18 @@ impl Board {
             .collect()
     }
 
+    fn collect_speed_zones(map: &Map) -> HashMap<Position, Vec<(SpeedZoneAffects, usize)>> {
+        let mut speed_zones = HashMap::<Position, Vec<_>>::default();
+
+        for (pos, elem) in map.position_element_iter() {
+            if let SpeedZone { affects, percent } = elem {
+                speed_zones.entry(*pos).or_default().push((*affects, *percent))
+            }
+        }
+
+        speed_zones
+    }
+
     pub fn position_is_wall(&self, pos: &Position) -> bool {
         self.wall_positions.contains(pos)
     }
@@ -60,4 +78,78 @@ impl Board {
     pub fn position_is_tunnel(&self, pos: &Position) -> bool {
         self.tunnel_positions.contains(pos)
     }
-}
\ No newline at end of file
+
+    pub fn position_is_red_zone(&self, pos: &Position) -> bool {
+        self.red_zone_positions.contains(pos)
+    }
+
+    /// Return the factor capmans speed gets multiplied with on the given position. Overlapping zones multiply.
+    pub fn capman_speed_modifier_at(&self, pos: &Position) -> f32 {
+        self.speed_modifier_at(pos, SpeedZoneAffects::Capman)
+    }
+
+    /// Return the factor a ghosts speed gets multiplied with on the given position. Overlapping zones multiply.
+    pub fn ghost_speed_modifier_at(&self, pos: &Position) -> f32 {
+        self.speed_modifier_at(pos, SpeedZoneAffects::Ghosts)
+    }
+
+    fn speed_modifier_at(&self, pos: &Position, mover: SpeedZoneAffects) -> f32 {
+        self.speed_zones
+            .get(pos)
+            .into_iter()
+            .flatten()
+            .filter(|(affects, _)| *affects == SpeedZoneAffects::All || *affects == mover)
+            .map(|(_, percent)| *percent as f32 / 100.0)
+            .product()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::position::Position;
+    use crate::map::board::Board;
+    use crate::map::Element::*;
+    use crate::map::SpeedZoneAffects::*;
+    use crate::map::{Field, Fields, Map};
+
+    fn create_board(elements: impl IntoIterator<Item = crate::map::Element>) -> Board {
+        let fields = elements
+            .into_iter()
+            .map(|element| Field {
+                position: Position::new(0, 0),
+                element,
+            })
+            .collect();
+        Board::new(&Map::new(&Fields(fields)))
+    }
+
+    #[test]
+    fn positions_without_speed_zones_keep_the_speed() {
+        let board = create_board([DotSpawn]);
+
+        assert_eq!(board.capman_speed_modifier_at(&Position::new(0, 0)), 1.0);
+        assert_eq!(board.ghost_speed_modifier_at(&Position::new(0, 0)), 1.0);
+    }
+
+    #[test]
+    fn speed_zones_only_affect_their_movers() {
+        let board = create_board([
+            SpeedZone { affects: Capman, percent: 50 },
+            SpeedZone { affects: Ghosts, percent: 150 },
+        ]);
+
+        assert_eq!(board.capman_speed_modifier_at(&Position::new(0, 0)), 0.5);
+        assert_eq!(board.ghost_speed_modifier_at(&Position::new(0, 0)), 1.5);
+    }
+
+    #[test]
+    fn overlapping_speed_zones_multiply() {
+        let board = create_board([
+            SpeedZone { affects: All, percent: 50 },
+            SpeedZone { affects: Ghosts, percent: 50 },
+        ]);
+
+        assert_eq!(board.capman_speed_modifier_at(&Position::new(0, 0)), 0.5);
+        assert_eq!(board.ghost_speed_modifier_at(&Position::new(0, 0)), 0.25);
+    }
+}
@@ -12,34 +12,95 @@ use Rotation::*;
 use crate::common::position::Position;
 use crate::common::Direction;
 use crate::game_assets::loaded_assets::LoadedAssets;
+use crate::ghosts::{Ghost, GhostId};
+use crate::is;
 use crate::life_cycle::LifeCycle::Loading;
 use crate::map::board::Board;
+use crate::map::navigation::NavigationGraph;
 
 pub mod board;
+pub mod navigation;
 
 pub struct MapPlugin;
 
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(JsonAssetPlugin::<Fields>::new(&["map.json"]))
+        app.insert_resource(MapName::from_args())
+            .add_plugin(JsonAssetPlugin::<Fields>::new(&["map.json"]))
             .add_system_set(SystemSet::on_exit(Loading).with_system(create_board_and_map));
     }
 }
 
+/// The name of the map the game is played on. The map is loaded from 'assets/maps/<name>.map.json'.
+#[derive(Clone, Debug, Deref, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MapName(pub String);
+
+impl MapName {
+    /// Passing '--map maze2' on the command line plays the map 'maze2'. The default map is 'default'.
+    fn from_args() -> Self {
+        let args = std::env::args().collect::<Vec<_>>();
+
+        args.iter()
+            .position(|arg| arg == "--map")
+            .and_then(|i| args.get(i + 1))
+            .map(|name| MapName(name.clone()))
+            .unwrap_or_default()
+    }
+
+    pub fn asset_path(&self) -> String {
+        format!("maps/{}.map.json", self.0)
+    }
+
+    /// Whether there is a map with this name in assets/maps. Only known once every asset is loaded.
+    pub fn exists(&self, game_asset_handles: &LoadedAssets) -> bool {
+        game_asset_handles.contains(self.asset_path())
+    }
+}
+
+impl Default for MapName {
+    fn default() -> Self {
+        MapName("default".to_string())
+    }
+}
+
+/// The map name from the command line is only checked when every asset is loaded. An unknown map gets reported,
+/// and the game is played on the default map instead.
 fn create_board_and_map(
     mut commands: Commands,
     game_asset_handles: Res<LoadedAssets>,
-    fields_assets: Res<Assets<Fields>>,
+    mut map_name: ResMut<MapName>,
 ) {
-    let fields = fields_assets
-        .get(&game_asset_handles.get_handle("maps/default.map.json"))
+    if !map_name.exists(&game_asset_handles) {
+        error!(
+            "there is no map '{}' in assets/maps, playing the default map instead",
+            **map_name
+        );
+        *map_name = MapName::default();
+    }
+
+    commands.add(build_board_and_map)
+}
+
+/// Build the map, the board and everything derived from them from the map the [MapName] resource names.
+///
+/// Used as command, so the map can also be switched while the game runs. The entities on the old board are
+/// left to the caller.
+pub fn build_board_and_map(world: &mut World) {
+    let handle = world
+        .resource::<LoadedAssets>()
+        .get_handle(world.resource::<MapName>().asset_path());
+    let fields = world
+        .resource::<Assets<Fields>>()
+        .get(&handle)
         .expect("the map should be loaded at this point");
-    let map = Map::new(&fields);
+    let map = Map::new(fields);
     let board = Board::new(&map);
     let board_dimensions = BoardDimensions::new(&board);
-    commands.insert_resource(map);
-    commands.insert_resource(board);
-    commands.insert_resource(board_dimensions)
+    let navigation_graph = NavigationGraph::new(&map, &board);
+    world.insert_resource(map);
+    world.insert_resource(board);
+    world.insert_resource(navigation_graph);
+    world.insert_resource(board_dimensions)
 }
 
 #[derive(Clone, Deref, Serialize, Deserialize, bevy::reflect::TypeUuid)]
@@ -49,20 +110,24 @@ struct Fields(Vec<Field>);
 /// Resource that knows the spawn locations of every entity, based on an external map file.
 ///
 /// The map should only be used to spawn or respawn entities into the world.
+///
+/// A position can hold more than one element, for example a dot in a red zone.
 pub struct Map {
-    elements_map: HashMap<Position, Element>,
+    elements_map: HashMap<Position, Vec<Element>>,
 }
 
 impl Map {
     fn new(fields: &Fields) -> Self {
-        Map {
-            elements_map: fields
-                .clone()
-                .0
-                .into_iter()
-                .map(|f| (f.position, f.element))
-                .collect(),
+        let mut elements_map = HashMap::<Position, Vec<Element>>::new();
+
+        for field in fields.iter() {
+            elements_map
+                .entry(field.position)
+                .or_default()
+                .push(field.element.clone());
         }
+
+        Map { elements_map }
     }
 
     pub(in crate::map) fn get_width(&self) -> usize {
@@ -88,13 +153,108 @@ impl Map {
     ) -> impl IntoIterator<Item = &Position> {
         self.elements_map
             .iter()
-            .filter(move |(_, elem)| (filter)(elem))
+            .filter(move |(_, elems)| elems.iter().any(&filter))
             .map(|(pos, _)| pos)
     }
 
+    /// Return every ghost declared on this map, ordered by their release order (which is also their id).
+    ///
+    /// Maps without ghost declarations get the four arcade ghosts, which scatter to the blinky, pinky, inky
+    /// and clyde corners.
+    pub fn ghosts(&self) -> Vec<GhostDeclaration> {
+        let mut declared = self
+            .position_element_iter()
+            .into_iter()
+            .filter_map(|(pos, elem)| match elem {
+                Element::GhostCorner {
+                    ghost,
+                    spawn_slot,
+                    release_order,
+                    house,
+                    brain,
+                    return_to,
+                } => Some((
+                    *release_order,
+                    GhostDeclaration {
+                        id: GhostId(0),
+                        kind: *ghost,
+                        corner: *pos,
+                        spawn_slot: *spawn_slot,
+                        house: *house,
+                        brain: brain.clone().unwrap_or_else(|| ghost.name().to_string()),
+                        return_to: *return_to,
+                    },
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if declared.is_empty() {
+            declared = self.arcade_ghosts();
+        }
+
+        // the position only makes the order deterministic if two ghosts share a release order
+        declared.sort_by_key(|(release_order, ghost)| (*release_order, ghost.corner.x, ghost.corner.y));
+        declared
+            .into_iter()
+            .enumerate()
+            .map(|(i, (_, ghost))| GhostDeclaration {
+                id: GhostId(i),
+                ..ghost
+            })
+            .collect()
+    }
+
+    fn arcade_ghosts(&self) -> Vec<(usize, GhostDeclaration)> {
+        let ghost = |release_order, kind: Ghost, filter: fn(&Element) -> bool, spawn_slot| {
+            let corner = *self
+                .get_positions_matching(filter)
+                .into_iter()
+                .next()
+                .expect("every ghost should have a corner");
+            let declaration = GhostDeclaration {
+                id: GhostId(0),
+                kind,
+                corner,
+                spawn_slot,
+                house: 0,
+                brain: kind.name().to_string(),
+                return_to: ReturnHouse::Own,
+            };
+            (release_order, declaration)
+        };
+
+        vec![
+            ghost(0, Ghost::Blinky, is!(Element::BlinkyCorner), SpawnSlot::InFrontOfEntrance),
+            ghost(1, Ghost::Pinky, is!(Element::PinkyCorner), SpawnSlot::Center),
+            ghost(2, Ghost::Inky, is!(Element::InkyCorner), SpawnSlot::Left),
+            ghost(3, Ghost::Clyde, is!(Element::ClydeCorner), SpawnSlot::Right),
+        ]
+    }
+
+    /// Return every element on the given position. Positions outside the map have none.
+    pub fn elements_at(&self, pos: &Position) -> &[Element] {
+        self.elements_map.get(pos).map(Vec::as_slice).unwrap_or(&[])
+    }
+
     /// Return an iterator over all positions and elements.
     pub fn position_element_iter(&self) -> impl IntoIterator<Item = (&Position, &Element)> {
-        self.elements_map.iter()
+        self.elements_map
+            .iter()
+            .flat_map(|(pos, elems)| elems.iter().map(move |elem| (pos, elem)))
+    }
+}
+
+#[cfg(test)]
+impl Map {
+    /// Load the default map directly from the assets, as tests have no asset server.
+    pub(crate) fn load_default() -> Self {
+        let json = include_str!("../../assets/maps/default.map.json");
+        Map::new(&serde_json::from_str(json).expect("the default map should be valid"))
+    }
+
+    pub(crate) fn from_fields(fields: Vec<Field>) -> Self {
+        Map::new(&Fields(fields))
     }
 }
 
@@ -104,7 +264,7 @@ pub struct Field {
     pub element: Element,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Element {
     Wall {
         wall_type: WallType,
@@ -129,6 +289,77 @@ pub enum Element {
     TunnelEntrance,
     TunnelHallway,
     InvisibleWall,
+    /// Ghosts in chase or scatter are not allowed to turn up on this position.
+    RedZone,
+    /// Declares a ghost of the given kind, which scatters to this position. He spawns on the given
+    /// slot in the given ghost house and gets released in the given order (lowest first).
+    ///
+    /// If a map declares no ghosts, the four arcade ghosts with their corners are used.
+    GhostCorner {
+        ghost: Ghost,
+        spawn_slot: SpawnSlot,
+        release_order: usize,
+        /// The index of the ghost house (see GhostHouses). Maps with only one house can omit it.
+        #[serde(default)]
+        house: usize,
+        /// The name of the brain (see GhostBrains) which decides how the ghost behaves. Without it,
+        /// the ghost gets the arcade brain of his kind.
+        #[serde(default)]
+        brain: Option<String>,
+        /// The house the ghost returns to when eaten. Without it, he returns to his own house.
+        #[serde(default)]
+        return_to: ReturnHouse,
+    },
+    /// Changes the speed of everything affected on this position to the given percentage of its current speed.
+    /// Below 100 slows down, above 100 speeds up.
+    SpeedZone {
+        affects: SpeedZoneAffects,
+        percent: usize,
+    },
+}
+
+/// The place in the ghost house where a ghost spawns. Several ghosts can share a slot.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum SpawnSlot {
+    /// In front of the ghost house entrance, where blinky spawns in the arcade.
+    InFrontOfEntrance,
+    /// The center of the house, where pinky spawns in the arcade.
+    Center,
+    /// Left of the center (seen from the entrance), where inky spawns in the arcade.
+    Left,
+    /// Right of the center (seen from the entrance), where clyde spawns in the arcade.
+    Right,
+}
+
+/// The ghost house an eaten ghost returns to.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum ReturnHouse {
+    /// The house the ghost belongs to.
+    #[default]
+    Own,
+    /// The house nearest to the place where the ghost got eaten.
+    Nearest,
+}
+
+/// A ghost the map declared.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GhostDeclaration {
+    pub id: GhostId,
+    pub kind: Ghost,
+    pub corner: Position,
+    pub spawn_slot: SpawnSlot,
+    pub house: usize,
+    /// The name of the ghosts brain (see GhostBrains)
+    pub brain: String,
+    pub return_to: ReturnHouse,
+}
+
+/// The movers a speed zone applies to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SpeedZoneAffects {
+    All,
+    Capman,
+    Ghosts,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Hash)]
@@ -170,3 +401,81 @@ macro_rules! is {
         }
     }};
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::common::position::Position;
+    use crate::ghosts::Ghost::*;
+    use crate::ghosts::GhostId;
+    use crate::map::Element::GhostCorner;
+    use crate::map::SpawnSlot::*;
+    use crate::map::{Field, Fields, Map, ReturnHouse};
+
+    #[test]
+    fn maps_without_declared_ghosts_have_the_arcade_ghosts() {
+        let map = Map::load_default();
+
+        let ghosts = map
+            .ghosts()
+            .into_iter()
+            .map(|g| (g.id, g.kind, g.spawn_slot))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            ghosts,
+            vec![
+                (GhostId(0), Blinky, InFrontOfEntrance),
+                (GhostId(1), Pinky, Center),
+                (GhostId(2), Inky, Left),
+                (GhostId(3), Clyde, Right),
+            ]
+        );
+    }
+
+    #[test]
+    fn declared_ghosts_are_ordered_by_their_release_order() {
+        let field = |x, ghost, spawn_slot, release_order| Field {
+            position: Position::new(x, 0),
+            element: GhostCorner {
+                ghost,
+                spawn_slot,
+                release_order,
+                house: 0,
+                brain: None,
+                return_to: ReturnHouse::Own,
+            },
+        };
+        let map = Map::new(&Fields(vec![
+            field(0, Clyde, Left, 2),
+            field(1, Blinky, InFrontOfEntrance, 0),
+            field(2, Blinky, Center, 1),
+        ]));
+
+        let ghosts = map.ghosts();
+
+        assert_eq!(ghosts.len(), 3);
+        assert_eq!((ghosts[0].kind, ghosts[0].corner), (Blinky, Position::new(1, 0)));
+        assert_eq!((ghosts[1].kind, ghosts[1].corner), (Blinky, Position::new(2, 0)));
+        assert_eq!((ghosts[2].id, ghosts[2].kind), (GhostId(2), Clyde));
+    }
+
+    #[test]
+    fn ghosts_get_the_arcade_brain_of_their_kind_unless_the_map_declares_another() {
+        let field = |x, release_order, brain: Option<&str>| Field {
+            position: Position::new(x, 0),
+            element: GhostCorner {
+                ghost: Clyde,
+                spawn_slot: Center,
+                release_order,
+                house: 0,
+                brain: brain.map(str::to_string),
+                return_to: ReturnHouse::Own,
+            },
+        };
+        let map = Map::new(&Fields(vec![field(0, 0, None), field(1, 1, Some("coward"))]));
+
+        let brains = map.ghosts().into_iter().map(|g| g.brain).collect::<Vec<_>>();
+
+        assert_eq!(brains, vec!["clyde", "coward"]);
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use bevy::utils::{HashMap, HashSet};
+
+use crate::common::position::Position;
+use crate::common::Direction;
+use crate::is;
+use crate::map::board::Board;
+use crate::map::{Element, Map};
+
+/// Resource which knows how every walkable position of the maze is connected, built once per map.
+///
+/// On the lowest level, the graph connects neighboured positions capman or the ghosts can walk on. Tunnels with the
+/// same index are linked, as walking into one tunnel leads out of the other. The ghost house and its entrance
+/// are connected too, but these edges can only be used by ghosts.
+///
+/// It is used to find the true walking distance between two positions (with breadth-first search),
+/// which, unlike the euclidean distance, does not lead ghosts into dead ends. As ghosts ask for distances
+/// every time they choose a way, the distance field of every destination is searched only once and cached.
+pub struct NavigationGraph {
+    edges: HashMap<Position, Vec<Edge>>,
+    /// The distance fields searched so far, by destination and if the ghost house may be passed
+    distance_fields: Mutex<HashMap<(Position, bool), HashMap<Position, usize>>>,
+}
+
+/// A connection from one position to a neighboured one.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Edge {
+    pub to: Position,
+    /// The direction to walk to reach the other position
+    pub direction: Direction,
+    pub kind: EdgeKind,
+}
+
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum EdgeKind {
+    /// A usual connection between two neighboured positions of the maze.
+    Corridor,
+    /// The link between two tunnels with the same index.
+    Tunnel,
+    /// A connection through the ghost house entrance or inside the ghost house. Only ghosts can use it.
+    GhostHouse,
+}
+
+impl NavigationGraph {
+    pub fn new(map: &Map, board: &Board) -> Self {
+        let maze = Self::collect_maze_positions(map, board);
+        let ghost_house = Self::collect_ghost_house_positions(board, &maze);
+        let tunnel_links = Self::create_tunnel_links(map);
+
+        let edges = maze
+            .iter()
+            .chain(ghost_house.iter())
+            .map(|pos| {
+                let neighbour_edges = pos
+                    .get_neighbours()
+                    .into_iter()
+                    .filter(|n| maze.contains(&n.position) || ghost_house.contains(&n.position))
+                    .map(|n| Edge {
+                        to: n.position,
+                        direction: n.direction,
+                        kind: match ghost_house.contains(pos) || ghost_house.contains(&n.position) {
+                            true => EdgeKind::GhostHouse,
+                            false => EdgeKind::Corridor,
+                        },
+                    });
+                let tunnel_edges = tunnel_links.get(pos).into_iter().flatten().copied();
+                (*pos, neighbour_edges.chain(tunnel_edges).collect())
+            })
+            .collect();
+
+        NavigationGraph {
+            edges,
+            distance_fields: Mutex::default(),
+        }
+    }
+
+    /// Collect every position that can be reached from capmans spawn without passing walls or the ghost house entrance.
+    fn collect_maze_positions(map: &Map, board: &Board) -> HashSet<Position> {
+        let start = map
+            .get_positions_matching(is!(Element::CapManSpawn))
+            .into_iter()
+            .copied();
+        Self::flood_fill(start, |pos| {
+            Self::is_on_board(board, pos) && !board.position_is_wall_or_entrance(pos)
+        })
+    }
+
+    /// Collect the ghost house entrance and every position behind it, which is not part of the maze.
+    fn collect_ghost_house_positions(board: &Board, maze: &HashSet<Position>) -> HashSet<Position> {
+        let entrances = (0..board.width as isize)
+            .flat_map(|x| (0..board.height as isize).map(move |y| Position::new(x, y)))
+            .filter(|pos| board.position_is_ghost_house_entrance(pos));
+        Self::flood_fill(entrances, |pos| {
+            Self::is_on_board(board, pos) && !board.position_is_wall(pos) && !maze.contains(pos)
+        })
+    }
+
+    fn flood_fill(
+        start: impl IntoIterator<Item = Position>,
+        walkable: impl Fn(&Position) -> bool,
+    ) -> HashSet<Position> {
+        let mut visited = HashSet::default();
+        let mut open = start.into_iter().collect::<VecDeque<_>>();
+
+        while let Some(position) = open.pop_front() {
+            if !visited.insert(position) {
+                continue;
+            }
+
+            open.extend(
+                position
+                    .get_neighbours()
+                    .into_iter()
+                    .map(|n| n.position)
+                    .filter(&walkable),
+            );
+        }
+
+        visited
+    }
+
+    fn is_on_board(board: &Board, position: &Position) -> bool {
+        position.x >= 0
+            && position.y >= 0
+            && position.x < board.width as isize
+            && position.y < board.height as isize
+    }
+
+    /// Link every tunnel to the other tunnels with the same index. Walking into a tunnel keeps the direction.
+    fn create_tunnel_links(map: &Map) -> HashMap<Position, Vec<Edge>> {
+        let tunnels = map
+            .position_element_iter()
+            .into_iter()
+            .filter_map(|(pos, elem)| match elem {
+                Element::Tunnel {
+                    index,
+                    opening_direction,
+                } => Some((*pos, *index, *opening_direction)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        tunnels
+            .iter()
+            .map(|(pos, index, opening_direction)| {
+                let links = tunnels
+                    .iter()
+                    .filter(|(other_pos, other_index, _)| other_index == index && other_pos != pos)
+                    .map(|(other_pos, _, _)| Edge {
+                        to: *other_pos,
+                        direction: *opening_direction,
+                        kind: EdgeKind::Tunnel,
+                    })
+                    .collect();
+                (*pos, links)
+            })
+            .collect()
+    }
+
+    pub fn contains(&self, position: &Position) -> bool {
+        self.edges.contains_key(position)
+    }
+
+    /// Return every connection from the given position to its neighbours.
+    pub fn edges(&self, position: &Position) -> &[Edge] {
+        self.edges.get(position).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Return the position of the graph with the smallest euclidean distance to the given position.
+    ///
+    /// Ghost targets are often outside the maze, this is where they are searched from instead.
+    pub fn nearest_position_to(&self, position: &Position) -> Position {
+        if self.contains(position) {
+            return *position;
+        }
+
+        *self
+            .edges
+            .keys()
+            .min_by_key(|pos| (pos.distance_to(position), pos.x, pos.y))
+            .expect("the navigation graph should not be empty")
+    }
+
+    /// Return the number of steps needed to walk from the given position to every reachable position.
+    ///
+    /// The ghost house can only be passed if explicitly allowed.
+    pub fn distance_field(
+        &self,
+        from: &Position,
+        through_ghost_house: bool,
+    ) -> HashMap<Position, usize> {
+        let mut visited = HashMap::default();
+        let mut open = VecDeque::from([(*from, 0)]);
+
+        while let Some((position, distance)) = open.pop_front() {
+            if visited.contains_key(&position) {
+                continue;
+            }
+            visited.insert(position, distance);
+
+            self.edges(&position)
+                .iter()
+                .filter(|e| through_ghost_house || e.kind != EdgeKind::GhostHouse)
+                .for_each(|e| open.push_back((e.to, distance + 1)));
+        }
+
+        visited
+    }
+
+    /// Return the number of steps needed to walk from one position to another, or None if there is no way.
+    ///
+    /// If the destination is not part of the graph, the distance to the nearest position of the graph is returned.
+    ///
+    /// Every edge can be walked both ways, so the distance is looked up in the distance field of the destination.
+    pub fn path_distance(
+        &self,
+        from: &Position,
+        to: &Position,
+        through_ghost_house: bool,
+    ) -> Option<usize> {
+        let to = self.nearest_position_to(to);
+        let mut distance_fields = self.distance_fields.lock().unwrap();
+        distance_fields
+            .entry((to, through_ghost_house))
+            .or_insert_with(|| self.distance_field(&to, through_ghost_house))
+            .get(from)
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::position::Position;
+    use crate::common::Direction::*;
+    use crate::map::board::Board;
+    use crate::map::navigation::{EdgeKind, NavigationGraph};
+    use crate::map::Map;
+
+    fn create_graph() -> NavigationGraph {
+        let map = Map::load_default();
+        let board = Board::new(&map);
+        NavigationGraph::new(&map, &board)
+    }
+
+    #[test]
+    fn walls_are_not_part_of_the_graph() {
+        let graph = create_graph();
+
+        assert!(graph.contains(&Position::new(3, 29)));
+        assert!(!graph.contains(&Position::new(2, 29)));
+    }
+
+    #[test]
+    fn the_ghost_house_can_only_be_passed_by_ghosts() {
+        let graph = create_graph();
+        let in_front_of_house = Position::new(15, 19);
+        let in_house = Position::new(15, 16);
+
+        assert!(graph.contains(&in_house));
+        assert!(graph
+            .edges(&in_front_of_house)
+            .iter()
+            .any(|e| e.direction == Down && e.kind == EdgeKind::GhostHouse));
+        assert_eq!(
+            graph.path_distance(&in_front_of_house, &in_house, true),
+            Some(3)
+        );
+        assert_eq!(
+            graph.path_distance(&in_front_of_house, &in_house, false),
+            None
+        );
+    }
+
+    #[test]
+    fn the_distance_follows_the_corridors() {
+        let graph = create_graph();
+
+        assert_eq!(
+            graph.path_distance(&Position::new(3, 29), &Position::new(10, 29), false),
+            Some(7)
+        );
+        // the direct line is blocked by a wall, so the way leads down to the next corridor and back up
+        assert_eq!(
+            graph.path_distance(&Position::new(14, 29), &Position::new(17, 29), false),
+            Some(11)
+        );
+    }
+
+    #[test]
+    fn the_distance_is_the_same_in_both_directions() {
+        let graph = create_graph();
+        let positions = [
+            Position::new(3, 29),
+            Position::new(2, 16),
+            Position::new(29, 16),
+            Position::new(15, 16),
+            Position::new(15, 19),
+        ];
+
+        for from in &positions {
+            for to in &positions {
+                for through_ghost_house in [false, true] {
+                    assert_eq!(
+                        graph.path_distance(from, to, through_ghost_house),
+                        graph.path_distance(to, from, through_ghost_house),
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tunnels_are_a_shortcut_to_the_other_side() {
+        let graph = create_graph();
+
+        assert_eq!(
+            graph.path_distance(&Position::new(2, 16), &Position::new(29, 16), false),
+            Some(5)
+        );
+        assert!(graph
+            .edges(&Position::new(0, 16))
+            .iter()
+            .any(|e| e.to == Position::new(31, 16) && e.kind == EdgeKind::Tunnel));
+    }
+
+    #[test]
+    fn targets_outside_the_graph_are_measured_to_the_nearest_position() {
+        let graph = create_graph();
+
+        assert_eq!(
+            graph.nearest_position_to(&Position::new(3, 33)),
+            Position::new(3, 29)
+        );
+        assert_eq!(
+            graph.path_distance(&Position::new(3, 25), &Position::new(3, 33), false),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn the_distance_field_contains_every_reachable_position() {
+        let graph = create_graph();
+        let field = graph.distance_field(&Position::new(3, 29), false);
+
+        assert_eq!(field.get(&Position::new(3, 29)), Some(&0));
+        assert_eq!(field.get(&Position::new(10, 29)), Some(&7));
+        assert_eq!(field.get(&Position::new(15, 16)), None);
+    }
+}
@@ -0,0 +1,514 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::net::TcpListener;
+
+use bevy::app::AppExit;
+use bevy::ecs::system::Resource;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::capman::{Capman, Dying};
+use crate::clock::{FixedTick, TimeScale};
+use crate::common::Direction;
+use crate::difficulty::Difficulty;
+use crate::director::{is_adaptive, set_adaptive, Performance};
+use crate::edibles::dots::EatenDots;
+use crate::edibles::energizer::EnergizerTimer;
+use crate::edibles::fruit::{Fruit, FruitDespawnTimer};
+use crate::edibles::Edible;
+use crate::ghost_house::CurrentHouse;
+use crate::ghost_house_gate::GhostHouseGates;
+use crate::ghosts::brain::{ArcadeAccuracy, BrainKind};
+use crate::ghosts::elroy::{Elroy, ElroySuspension};
+use crate::ghosts::movement::GhostsFrozen;
+use crate::ghosts::schedule::Schedule;
+use crate::ghosts::state::{State as GhostState, StateOverrides};
+use crate::ghosts::target::Target;
+use crate::ghosts::{Ghost, GhostId};
+use crate::interactions::GodMode;
+use crate::level::Level;
+use crate::life_cycle::{LifeCycle, StateTimer};
+use crate::lives::{Life, PointsRequiredForExtraLife};
+use crate::map::MapName;
+use crate::network::protocol::{Connection, Message};
+use crate::players::input::{InputBuffer, KeyBindings, PlayerInputs};
+use crate::players::GameMode::*;
+use crate::players::Player::*;
+use crate::players::{GameMode, Player};
+use crate::random::Random;
+use crate::score::{EatenGhostCounter, Score};
+use crate::speed::Speed;
+
+mod protocol;
+
+/// The number of ticks the game advances per second in a networked game.
+const TICKS_PER_SECOND: u32 = 60;
+
+/// Inputs are applied this many ticks after they were pressed, so they have time to reach the other instance
+/// before it needs them. A higher delay makes the game less responsive, but stalls less on slow connections.
+const INPUT_DELAY: usize = 3;
+
+/// Lets two game instances play co-op or versus across machines.
+///
+/// The game uses lockstep: both instances simulate the whole game and only exchange the inputs of their local
+/// player. A tick is only simulated once the inputs of both players for this tick are known, which keeps
+/// both games exactly in sync, as long as the simulation is deterministic (seeded random and fixed tick).
+///
+/// Start one instance with '--host ADDRESS' and the other with '--connect ADDRESS'. The host decides the
+/// game mode ('--coop' or '--versus') and every other setting of the game, and controls player one.
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(role) = NetworkRole::from_args() {
+            app.insert_resource(role)
+                .insert_resource(FixedTick::per_second(TICKS_PER_SECOND));
+        }
+
+        app.add_startup_system_to_stage(
+            StartupStage::PreStartup,
+            connect_to_other_instance.exclusive_system(),
+        )
+        .add_system_to_stage(
+            CoreStage::PreUpdate,
+            exchange_inputs_with_other_instance
+                .exclusive_system()
+                .at_end(),
+        )
+        .add_system_to_stage(CoreStage::Last, log_state_hash.exclusive_system());
+    }
+}
+
+/// Whether this instance hosts the game or connects to a host. Only exists until both instances are connected.
+enum NetworkRole {
+    Host(TcpListener),
+    Client(String),
+}
+
+impl NetworkRole {
+    fn from_args() -> Option<Self> {
+        let args = std::env::args().collect::<Vec<_>>();
+        let value_of = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .and_then(|i| args.get(i + 1))
+                .cloned()
+        };
+
+        value_of("--host")
+            .map(|address| {
+                NetworkRole::Host(
+                    TcpListener::bind(&address).expect("the host address should be free"),
+                )
+            })
+            .or_else(|| value_of("--connect").map(NetworkRole::Client))
+    }
+}
+
+/// Wait for the other instance and start the lockstep with it.
+///
+/// Runs once before every other startup system, so the seed and settings agreed on are used from the start. The window stays empty until both instances are connected.
+fn connect_to_other_instance(world: &mut World) {
+    let role = match world.remove_resource::<NetworkRole>() {
+        Some(role) => role,
+        None => return,
+    };
+
+    let lockstep = match role {
+        NetworkRole::Host(listener) => {
+            info!(
+                "Waiting for the other player to connect to {:?}",
+                listener.local_addr()
+            );
+            let connection =
+                Connection::accept(&listener).expect("the other player should connect");
+            Lockstep::new(greet_client(world, connection), One)
+        }
+        NetworkRole::Client(address) => {
+            let connection = Connection::connect(&address).expect("the host should be reachable");
+            Lockstep::new(receive_greeting(world, connection), Two)
+        }
+    };
+
+    world.insert_resource(lockstep)
+}
+
+/// Tell the other instance which seed and settings to use.
+///
+/// A networked game always has two players, so single player becomes co-op.
+fn greet_client(world: &mut World, mut connection: Connection) -> Connection {
+    let game_mode = match *world.resource::<GameMode>() {
+        SinglePlayer => Coop,
+        mode => mode,
+    };
+    connection
+        .send(&Message::Hello {
+            seed: world.resource::<Random>().seed(),
+            game_mode,
+            difficulty: *world.resource::<Difficulty>(),
+            brain_kind: *world.resource::<BrainKind>(),
+            arcade_accuracy: *world.resource::<ArcadeAccuracy>(),
+            adaptive: is_adaptive(world),
+            map_name: world.resource::<MapName>().clone(),
+        })
+        .expect("the other player should be reachable");
+
+    world.insert_resource(game_mode);
+    connection
+}
+
+/// Take over the seed and settings of the host, whatever this instance was started with.
+fn receive_greeting(world: &mut World, mut connection: Connection) -> Connection {
+    match connection.receive().expect("the host should greet") {
+        Message::Hello {
+            seed,
+            game_mode,
+            difficulty,
+            brain_kind,
+            arcade_accuracy,
+            adaptive,
+            map_name,
+        } => {
+            world.insert_resource(Random::from_seed(seed));
+            world.insert_resource(game_mode);
+            world.insert_resource(difficulty);
+            world.insert_resource(brain_kind);
+            world.insert_resource(arcade_accuracy);
+            set_adaptive(world, adaptive);
+            world.insert_resource(map_name);
+        }
+        message => panic!("expected a greeting from the host, got {:?}", message),
+    }
+
+    connection
+}
+
+/// The state of the lockstep between the two instances.
+pub struct Lockstep {
+    connection: Connection,
+    local_player: Player,
+    key_bindings: KeyBindings,
+    /// The tick that gets simulated next.
+    tick: usize,
+    local_inputs: HashMap<usize, Option<Direction>>,
+    remote_inputs: HashMap<usize, Option<Direction>>,
+}
+
+impl Lockstep {
+    pub fn new(connection: Connection, local_player: Player) -> Self {
+        Lockstep {
+            connection,
+            local_player,
+            // Every player has their own keyboard, so they can use all keys
+            key_bindings: KeyBindings::for_player(local_player, &SinglePlayer),
+            tick: 0,
+            local_inputs: HashMap::default(),
+            remote_inputs: HashMap::default(),
+        }
+    }
+
+    fn remote_player(&self) -> Player {
+        match self.local_player {
+            One => Two,
+            Two => One,
+        }
+    }
+
+    /// Send the currently pressed direction, which will be applied INPUT_DELAY ticks from now.
+    fn send_local_input(&mut self, direction: Option<Direction>) -> io::Result<()> {
+        let tick = self.tick + INPUT_DELAY;
+        self.local_inputs.insert(tick, direction);
+        self.connection.send(&Message::Input { tick, direction })
+    }
+
+    /// Return the direction the other player pressed for the current tick. Blocks until it arrived.
+    ///
+    /// Nobody could press anything for the first ticks, because of the input delay.
+    fn receive_remote_input(&mut self) -> io::Result<Option<Direction>> {
+        if self.tick < INPUT_DELAY {
+            return Ok(None);
+        }
+
+        loop {
+            if let Some(direction) = self.remote_inputs.remove(&self.tick) {
+                return Ok(direction);
+            }
+
+            match self.connection.receive()? {
+                Message::Input { tick, direction } => {
+                    self.remote_inputs.insert(tick, direction);
+                }
+                message => warn!("Unexpected message from the other player: {:?}", message),
+            }
+        }
+    }
+
+    /// Exchange inputs for the current tick and return the inputs of both players.
+    fn advance(&mut self, keyboard_input: &Input<KeyCode>) -> io::Result<PlayerInputs> {
+        self.send_local_input(self.key_bindings.pressed_direction(keyboard_input))?;
+        let remote_direction = self.receive_remote_input()?;
+        let local_direction = self.local_inputs.remove(&self.tick).flatten();
+        self.tick += 1;
+
+        let mut inputs = PlayerInputs::default();
+        inputs.set(self.local_player, local_direction);
+        inputs.set(self.remote_player(), remote_direction);
+        Ok(inputs)
+    }
+}
+
+/// Replace the inputs collected from the local keyboard with the inputs both instances agreed on.
+///
+/// While loading, the instances do not wait for each other, as loading takes a different time on every machine
+/// and nothing can be controlled yet. The lockstep starts in the first frame after loading.
+///
+/// Runs exclusively at the end of the stage, so it comes after the keyboard inputs were collected and blocks only the
+/// app's own thread while it waits for the other instance, instead of a thread of the shared task pool.
+fn exchange_inputs_with_other_instance(
+    keyboard_input: Res<Input<KeyCode>>,
+    life_cycle: Res<State<LifeCycle>>,
+    lockstep: Option<ResMut<Lockstep>>,
+    mut player_inputs: ResMut<PlayerInputs>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let mut lockstep = match lockstep {
+        Some(lockstep) => lockstep,
+        None => return,
+    };
+
+    if life_cycle.current() == &LifeCycle::Loading {
+        *player_inputs = PlayerInputs::default();
+        return;
+    }
+
+    match lockstep.advance(&keyboard_input) {
+        Ok(inputs) => *player_inputs = inputs,
+        Err(e) => {
+            error!("Lost the connection to the other player: {}", e);
+            exit.send(AppExit)
+        }
+    }
+}
+
+/// Log a hash of the game state, so the logs of both instances can be compared to find out when they went out of sync.
+fn log_state_hash(world: &mut World) {
+    let tick = match world.get_resource::<Lockstep>() {
+        Some(lockstep) => lockstep.tick,
+        None => return,
+    };
+    debug!("State hash at tick {}: {:x}", tick, hash_game_state(world));
+}
+
+/// Hash everything that must be equal on both instances after every tick: every capman, ghost, edible and life,
+/// the settings and progress of the game, every timer and counter and the state of the random number generator.
+///
+/// Values are hashed through their Debug output, which also covers floats and timers. Entities are left out, as
+/// they only need to match in what they are, not in their ids.
+pub fn hash_game_state(world: &mut World) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    hash_sorted(
+        world
+            .query_filtered::<(
+                &Player,
+                &Transform,
+                &Direction,
+                Option<&Speed>,
+                Option<&InputBuffer>,
+                Option<&Dying>,
+            ), With<Capman>>()
+            .iter(world),
+        &mut hasher,
+    );
+    hash_sorted(
+        world
+            .query::<(
+                &Ghost,
+                &GhostId,
+                &Transform,
+                &Direction,
+                &GhostState,
+                &Target,
+                Option<&Speed>,
+                Option<&Elroy>,
+                Option<&InputBuffer>,
+                Option<&CurrentHouse>,
+            )>()
+            .iter(world),
+        &mut hasher,
+    );
+    hash_sorted(
+        world
+            .query_filtered::<(&Transform, Option<&Fruit>), With<Edible>>()
+            .iter(world),
+        &mut hasher,
+    );
+    hash_sorted(world.query::<(&Player, &Life)>().iter(world), &mut hasher);
+
+    hash_resource::<GameMode>(world, &mut hasher);
+    hash_resource::<Difficulty>(world, &mut hasher);
+    hash_resource::<BrainKind>(world, &mut hasher);
+    hash_resource::<ArcadeAccuracy>(world, &mut hasher);
+    hash_resource::<MapName>(world, &mut hasher);
+    hash_resource::<State<LifeCycle>>(world, &mut hasher);
+    hash_resource::<StateTimer>(world, &mut hasher);
+    hash_resource::<Random>(world, &mut hasher);
+    hash_resource::<Level>(world, &mut hasher);
+    hash_resource::<Score>(world, &mut hasher);
+    hash_resource::<EatenGhostCounter>(world, &mut hasher);
+    hash_resource::<PointsRequiredForExtraLife>(world, &mut hasher);
+    hash_resource::<EatenDots>(world, &mut hasher);
+    hash_resource::<EnergizerTimer>(world, &mut hasher);
+    hash_resource::<FruitDespawnTimer>(world, &mut hasher);
+    hash_resource::<Schedule>(world, &mut hasher);
+    hash_resource::<StateOverrides>(world, &mut hasher);
+    hash_resource::<GhostHouseGates>(world, &mut hasher);
+    hash_resource::<ElroySuspension>(world, &mut hasher);
+    hash_resource::<GhostsFrozen>(world, &mut hasher);
+    hash_resource::<GodMode>(world, &mut hasher);
+    hash_resource::<Performance>(world, &mut hasher);
+    hash_resource::<TimeScale>(world, &mut hasher);
+
+    hasher.finish()
+}
+
+/// Hash the values regardless of their order, as queries return them in the order of their entities.
+fn hash_sorted(values: impl Iterator<Item = impl Debug>, hasher: &mut impl Hasher) {
+    let mut values = values
+        .map(|value| format!("{:?}", value))
+        .collect::<Vec<_>>();
+    values.sort();
+    values.hash(hasher)
+}
+
+/// Missing resources are hashed too, as a resource might only exist on one instance.
+fn hash_resource<R: Resource + Debug>(world: &World, hasher: &mut impl Hasher) {
+    format!("{:?}", world.get_resource::<R>()).hash(hasher)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    use bevy::app::AppExit;
+    use bevy::prelude::*;
+
+    use crate::capman::Capman;
+    use crate::director::set_adaptive;
+    use crate::game::headless_app;
+    use crate::ghosts::brain::{ArcadeAccuracy, BrainKind};
+    use crate::life_cycle::LifeCycle;
+    use crate::map::MapName;
+    use crate::network::{hash_game_state, Lockstep, NetworkRole, TICKS_PER_SECOND};
+    use crate::random::Random;
+
+    const TICKS: usize = 900;
+
+    #[test]
+    fn two_instances_stay_in_sync_over_loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let finished = Arc::new(Barrier::new(2));
+
+        // the instances wait for each other every tick, so they have to run at the same time.
+        // The client starts with another seed and other settings, as it takes them from the host.
+        let host_finished = finished.clone();
+        let host = thread::spawn(move || {
+            run_game(
+                NetworkRole::Host(listener),
+                |app| {
+                    app.insert_resource(Random::from_seed(42));
+                },
+                &[(20, KeyCode::Left), (300, KeyCode::Up)],
+                &host_finished,
+            )
+        });
+        let client = thread::spawn(move || {
+            run_game(
+                NetworkRole::Client(address),
+                |app| {
+                    app.insert_resource(Random::from_seed(7))
+                        .insert_resource(BrainKind::Pathfinding)
+                        .insert_resource(ArcadeAccuracy {
+                            up_overflow_bug: true,
+                        })
+                        .insert_resource(MapName("maze2".to_string()));
+                    set_adaptive(&mut app.world, true);
+                },
+                &[(50, KeyCode::Right), (400, KeyCode::Down)],
+                &finished,
+            )
+        });
+        let (host_hashes, host_moved) = host.join().unwrap();
+        let (client_hashes, client_moved) = client.join().unwrap();
+
+        assert_eq!(host_hashes.len(), TICKS);
+        for (tick, (host_hash, client_hash)) in host_hashes.iter().zip(&client_hashes).enumerate() {
+            assert_eq!(host_hash, client_hash, "out of sync at tick {}", tick);
+        }
+        assert!(host_moved && client_moved, "the capmen should have moved");
+    }
+
+    /// Run the whole game without window, rendering and sound until the lockstep simulated TICKS ticks.
+    /// The given function sets the instance up before it connects. Keys are pressed at the given ticks.
+    ///
+    /// Returns the hash of the game state after every tick and whether the capmen moved.
+    fn run_game(
+        role: NetworkRole,
+        set_up: impl FnOnce(&mut App),
+        key_presses: &[(usize, KeyCode)],
+        finished: &Barrier,
+    ) -> (Vec<u64>, bool) {
+        let mut app = headless_app(TICKS_PER_SECOND);
+        app.insert_resource(role);
+        set_up(&mut app);
+
+        let mut hashes = Vec::new();
+        let mut start_positions = None;
+        while hashes.len() < TICKS {
+            let tick = app
+                .world
+                .get_resource::<Lockstep>()
+                .map_or(0, |lockstep| lockstep.tick);
+            if let Some((_, key)) = key_presses.iter().find(|(at, _)| *at == tick) {
+                let mut keyboard_input = app.world.resource_mut::<Input<KeyCode>>();
+                keyboard_input.release_all();
+                keyboard_input.press(*key);
+            }
+
+            app.update();
+            assert!(
+                app.world.resource::<Events<AppExit>>().is_empty(),
+                "lost the connection to the other instance"
+            );
+
+            if app.world.resource::<Lockstep>().tick > tick {
+                hashes.push(hash_game_state(&mut app.world));
+            }
+            if start_positions.is_none()
+                && app.world.resource::<State<LifeCycle>>().current() == &LifeCycle::Running
+            {
+                start_positions = Some(capman_positions(&mut app));
+            }
+        }
+
+        let moved =
+            start_positions.is_some_and(|positions| positions != capman_positions(&mut app));
+
+        // one instance may be a few ticks ahead, so the connection must stay open until the other one caught up
+        finished.wait();
+        (hashes, moved)
+    }
+
+    fn capman_positions(app: &mut App) -> Vec<Vec3> {
+        let mut query = app.world.query_filtered::<&Transform, With<Capman>>();
+        query
+            .iter(&app.world)
+            .map(|transform| transform.translation)
+            .collect()
+    }
+}
@@ -0,0 +1,83 @@
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::Direction;
+use crate::difficulty::Difficulty;
+use crate::ghosts::brain::{ArcadeAccuracy, BrainKind};
+use crate::map::MapName;
+use crate::players::GameMode;
+
+/// How long to wait for the other instance before the connection is considered lost.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Everything two game instances tell each other. Every message is sent as one line of json.
+///
+/// The whole game is simulated on both instances, so besides the initial handshake only the
+/// inputs of the players and the tick they belong to are exchanged.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Message {
+    /// Sent by the host once the other instance connected. Both instances start from the same seed and play
+    /// with the same settings.
+    Hello {
+        seed: u64,
+        game_mode: GameMode,
+        difficulty: Difficulty,
+        brain_kind: BrainKind,
+        arcade_accuracy: ArcadeAccuracy,
+        adaptive: bool,
+        map_name: MapName,
+    },
+    /// The direction the local player pressed, which gets applied in the given tick on both instances.
+    Input {
+        tick: usize,
+        direction: Option<Direction>,
+    },
+}
+
+/// A connection to another game instance.
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    /// Wait until another instance connects to the given listener.
+    pub fn accept(listener: &TcpListener) -> io::Result<Self> {
+        let (stream, _) = listener.accept()?;
+        Self::from_stream(stream)
+    }
+
+    pub fn connect(address: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::from_stream(TcpStream::connect(address)?)
+    }
+
+    fn from_stream(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+
+        Ok(Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())
+    }
+
+    /// Block until the next message arrives.
+    pub fn receive(&mut self) -> io::Result<Message> {
+        let mut line = String::new();
+
+        match self.reader.read_line(&mut line)? {
+            0 => Err(io::ErrorKind::UnexpectedEof.into()),
+            _ => Ok(serde_json::from_str(&line)?),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::common::Direction;
+use crate::common::Direction::*;
+use crate::players::GameMode::*;
+use crate::players::Player::*;
+use crate::players::{GameMode, Player};
+
+/// The keys a player uses to steer the entity they control.
+#[derive(Clone, Component)]
+pub struct KeyBindings {
+    up: Vec<KeyCode>,
+    down: Vec<KeyCode>,
+    left: Vec<KeyCode>,
+    right: Vec<KeyCode>,
+}
+
+impl KeyBindings {
+    /// A single player can use the arrow keys and WASD. If two players share the keyboard,
+    /// player one uses WASD and player two the arrow keys.
+    pub fn for_player(player: Player, game_mode: &GameMode) -> Self {
+        match (game_mode, player) {
+            (SinglePlayer, _) => KeyBindings {
+                up: vec![KeyCode::Up, KeyCode::W],
+                down: vec![KeyCode::Down, KeyCode::S],
+                left: vec![KeyCode::Left, KeyCode::A],
+                right: vec![KeyCode::Right, KeyCode::D],
+            },
+            (_, One) => KeyBindings {
+                up: vec![KeyCode::W],
+                down: vec![KeyCode::S],
+                left: vec![KeyCode::A],
+                right: vec![KeyCode::D],
+            },
+            (_, Two) => KeyBindings {
+                up: vec![KeyCode::Up],
+                down: vec![KeyCode::Down],
+                left: vec![KeyCode::Left],
+                right: vec![KeyCode::Right],
+            },
+        }
+    }
+
+    /// Return the direction which key is currently pressed, if any.
+    pub fn pressed_direction(&self, keyboard_input: &Input<KeyCode>) -> Option<Direction> {
+        [
+            (&self.left, Left),
+            (&self.right, Right),
+            (&self.up, Up),
+            (&self.down, Down),
+        ]
+        .into_iter()
+        .find(|(keys, _)| keyboard_input.any_pressed(keys.iter().copied()))
+        .map(|(_, direction)| direction)
+    }
+}
+
+/// The direction every player wants to go in the current frame.
+///
+/// Game systems never read the keyboard directly, but only these inputs. Locally they are collected from the keyboard,
+/// in a networked game they are the inputs both instances agreed on for the current tick.
+#[derive(Default)]
+pub struct PlayerInputs(HashMap<Player, Direction>);
+
+impl PlayerInputs {
+    pub fn pressed_direction(&self, player: &Player) -> Option<Direction> {
+        self.0.get(player).copied()
+    }
+
+    pub fn set(&mut self, player: Player, direction: Option<Direction>) {
+        match direction {
+            Some(dir) => self.0.insert(player, dir),
+            None => self.0.remove(&player),
+        };
+    }
+}
+
+/// Label for the system that collects the players inputs. Systems that overwrite the inputs must run after it.
+#[derive(Clone, Hash, Debug, Eq, PartialEq, SystemLabel)]
+pub struct LPlayerInputs;
+
+/// Collect the pressed directions of every player that steers an entity with the keyboard.
+pub(in crate::players) fn collect_keyboard_inputs(
+    keyboard_input: Res<Input<KeyCode>>,
+    query: Query<(&Player, &KeyBindings)>,
+    mut player_inputs: ResMut<PlayerInputs>,
+) {
+    *player_inputs = PlayerInputs::default();
+
+    for (player, key_bindings) in &query {
+        player_inputs.set(*player, key_bindings.pressed_direction(&keyboard_input));
+    }
+}
+
+/// Saves the wished direction an entity should move to next.
+///
+/// If a turn is not possible right now (because of a wall or the entity is not centered enough),
+/// the direction is buffered and retried until the turn is possible or another key was pressed.
+#[derive(Component, Debug, Default, Deref, DerefMut)]
+pub struct InputBuffer(pub Option<Direction>);
+
+/// Return the direction the entity should move to next. If the player pressed no direction, return the last buffered input.
+pub fn get_wished_direction(
+    player_inputs: &PlayerInputs,
+    player: &Player,
+    input_buffer: &InputBuffer,
+) -> Option<Direction> {
+    player_inputs.pressed_direction(player).or(**input_buffer)
+}
@@ -0,0 +1,84 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::ghosts::Ghost;
+use crate::players::input::{collect_keyboard_inputs, LPlayerInputs, PlayerInputs};
+use crate::players::GameMode::*;
+use crate::players::Player::*;
+
+pub mod input;
+
+pub struct PlayersPlugin;
+
+impl Plugin for PlayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameMode::from_args())
+            .init_resource::<PlayerInputs>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                collect_keyboard_inputs
+                    .label(LPlayerInputs)
+                    .after(InputSystem),
+            );
+    }
+}
+
+/// Identifies a human player. Every capman, life and score belongs to exactly one player.
+#[derive(Copy, Clone, Component, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    /// The color capmans sprite gets multiplied with, so players can tell their capmen apart.
+    pub fn tint(&self) -> Color {
+        match self {
+            One => Color::WHITE,
+            Two => Color::rgb(0.4, 1.0, 1.0),
+        }
+    }
+}
+
+/// Resource that tells how many players take part in the game and what they control.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum GameMode {
+    /// One player controls one capman.
+    SinglePlayer,
+    /// Two players control a capman each on the same maze, with their own lives and score.
+    Coop,
+    /// Player one controls capman, player two controls Blinky and hunts him.
+    Versus,
+}
+
+impl GameMode {
+    /// Read the game mode from the command line. Passing '--coop' or '--versus' starts a game for two players.
+    fn from_args() -> Self {
+        let args = std::env::args().collect::<Vec<_>>();
+
+        if args.iter().any(|arg| arg == "--coop") {
+            Coop
+        } else if args.iter().any(|arg| arg == "--versus") {
+            Versus
+        } else {
+            SinglePlayer
+        }
+    }
+
+    /// Return every player that controls a capman in this mode.
+    pub fn capman_players(&self) -> Vec<Player> {
+        match self {
+            SinglePlayer | Versus => vec![One],
+            Coop => vec![One, Two],
+        }
+    }
+
+    /// Return the player that controls the given ghost, if the ghost is not controlled by the computer.
+    pub fn player_controlling(&self, ghost: &Ghost) -> Option<Player> {
+        match (self, ghost) {
+            (Versus, Ghost::Blinky) => Some(Two),
+            _ => None,
+        }
+    }
+}
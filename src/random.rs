@@ -1,19 +1,59 @@
+use std::fmt::{Debug, Formatter};
+
 use bevy::prelude::*;
 use rand::prelude::*;
+use rand::rngs::StdRng;
 
 pub struct RandomPlugin;
 
 impl Plugin for RandomPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Random);
+        app.insert_resource(Random::from_seed(seed_from_args().unwrap_or_else(random)));
     }
 }
 
-pub struct Random;
+/// Read the seed from the command line, passed like '--seed 42'.
+fn seed_from_args() -> Option<u64> {
+    let args = std::env::args().collect::<Vec<_>>();
+    args.iter()
+        .position(|arg| arg == "--seed")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|seed| seed.parse().ok())
+}
+
+/// The only source of randomness in the game.
+///
+/// The generator is seeded, so two games started with the same seed and the same inputs play out exactly the same.
+/// This is required for networked games, where every instance simulates the whole game on its own.
+pub struct Random {
+    seed: u64,
+    rng: StdRng,
+}
 
 impl Random {
-    pub fn zero_to(&self, n: usize) -> usize {
-        let mut rng = thread_rng();
-        rng.gen_range(0..n)
+    pub fn from_seed(seed: u64) -> Self {
+        Random {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
     }
-}
\ No newline at end of file
+
+    pub fn zero_to(&mut self, n: usize) -> usize {
+        self.rng.gen_range(0..n)
+    }
+}
+
+/// The generator itself does not show its state, so the number it would return next is shown instead. Generators in
+/// the same state always look the same, which networked games use to check that their instances are in sync.
+impl Debug for Random {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Random")
+            .field("seed", &self.seed)
+            .field("next", &self.rng.clone().next_u64())
+            .finish()
+    }
+}
@@ -1,42 +1,68 @@
 use crate::board_dimensions::BoardDimensions;
 use bevy::prelude::*;
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 use crate::constants::{FONT, POINTS_PER_DOT, POINTS_PER_ENERGIZER, POINTS_PER_GHOST, TEXT_Z};
-use crate::edibles::energizer::EnergizerOver;
+use crate::edibles::energizer::{EnergizerOver, LEnergizerTimer};
 use crate::edibles::fruit::Fruit::*;
 use crate::game_assets::loaded_assets::LoadedAssets;
-use crate::interactions::{EDotEaten, EEnergizerEaten, EFruitEaten, EGhostEaten};
+use crate::interactions::{
+    EDotEaten, EEnergizerEaten, EFruitEaten, EGhostEaten, LCapmanInteractions,
+};
 use crate::life_cycle::LifeCycle::{CapmanHit, Running, Start};
+use crate::players::Player::*;
+use crate::players::{GameMode, Player};
 
 pub struct ScorePlugin;
 
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Score(0))
+        app.insert_resource(Score::default())
             .insert_resource(EatenGhostCounter(0))
             .add_system_set(SystemSet::on_enter(Start).with_system(create_score_boards))
             .add_system_set(
                 SystemSet::on_update(Running)
-                    .with_system(update_scoreboard)
                     .with_system(add_points_for_eaten_dot)
                     .with_system(add_points_for_eaten_energizer)
                     .with_system(add_points_for_eaten_ghost_and_display_score_text)
-                    .with_system(reset_eaten_ghost_counter_when_energizer_is_over)
                     .with_system(add_points_for_eaten_fruit_and_display_score_text)
+                    .label(LScore)
+                    .after(LCapmanInteractions),
+            )
+            .add_system_set(
+                SystemSet::on_update(Running)
+                    .with_system(update_scoreboard.after(LScore))
+                    .with_system(
+                        reset_eaten_ghost_counter_when_energizer_is_over
+                            .after(LScore)
+                            .after(LEnergizerTimer),
+                    )
                     .with_system(update_score_texts),
             )
             .add_system_set(SystemSet::on_enter(CapmanHit).with_system(despawn_score_texts));
     }
 }
 
-/// Resource that saves how many points the player has collected so far
-#[derive(Deref, DerefMut)]
-pub struct Score(usize);
+/// Marks every system that adds points. Systems reading the score run after them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub struct LScore;
+
+/// Resource that saves how many points each player has collected so far
+#[derive(Debug, Default)]
+pub struct Score(BTreeMap<Player, usize>);
 
 impl Score {
-    fn add(&mut self, points: usize) {
-        **self += points
+    fn add(&mut self, player: Player, points: usize) {
+        *self.0.entry(player).or_insert(0) += points
+    }
+
+    pub fn set(&mut self, player: Player, points: usize) {
+        self.0.insert(player, points);
+    }
+
+    pub fn of(&self, player: &Player) -> usize {
+        *self.0.get(player).unwrap_or(&0)
     }
 }
 
@@ -49,34 +75,27 @@ pub struct ScoreText;
 #[derive(Component, Deref, DerefMut)]
 pub struct ScoreTextTimer(Timer);
 
-#[derive(Deref, DerefMut)]
-struct EatenGhostCounter(usize);
+#[derive(Debug, Deref, DerefMut)]
+pub struct EatenGhostCounter(usize);
 
 fn create_score_boards(
     mut commands: Commands,
     game_asset_handles: Res<LoadedAssets>,
+    game_mode: Res<GameMode>,
     dimensions: Res<BoardDimensions>,
+    score: Res<Score>,
 ) {
     let origin = dimensions.origin();
 
-    commands
-        .spawn_bundle(Text2dBundle {
-            text: Text::from_section(
-                "0".to_string(),
-                TextStyle {
-                    font: game_asset_handles.get_handle(FONT),
-                    font_size: 20.0,
-                    color: Color::rgb(1.0, 1.0, 1.0),
-                },
-            )
-            .with_alignment(TextAlignment {
-                vertical: VerticalAlign::Center,
-                horizontal: HorizontalAlign::Left,
-            }),
-            transform: Transform::from_xyz(origin.x, origin.y + dimensions.board_height(), 0.0),
-            ..Default::default()
-        })
-        .insert(ScoreBoard);
+    for player in game_mode.capman_players() {
+        spawn_score_board(
+            &mut commands,
+            &game_asset_handles,
+            &dimensions,
+            &score,
+            player,
+        );
+    }
 
     commands.spawn_bundle(Text2dBundle {
         text: Text::from_section(
@@ -100,28 +119,76 @@ fn create_score_boards(
     });
 }
 
-fn update_scoreboard(score: Res<Score>, mut query: Query<&mut Text, With<ScoreBoard>>) {
+/// Player ones score is displayed on the top left of the board, player twos on the top right. The boards start with
+/// the current score, as the console can switch the map and start the game again.
+fn spawn_score_board(
+    commands: &mut Commands,
+    game_asset_handles: &LoadedAssets,
+    dimensions: &BoardDimensions,
+    score: &Score,
+    player: Player,
+) {
+    let origin = dimensions.origin();
+    let (x, horizontal) = match player {
+        One => (origin.x, HorizontalAlign::Left),
+        Two => (origin.x + dimensions.board_width(), HorizontalAlign::Right),
+    };
+
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                score.of(&player).to_string(),
+                TextStyle {
+                    font: game_asset_handles.get_handle(FONT),
+                    font_size: 20.0,
+                    color: Color::rgb(1.0, 1.0, 1.0),
+                },
+            )
+            .with_alignment(TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal,
+            }),
+            transform: Transform::from_xyz(x, origin.y + dimensions.board_height(), 0.0),
+            ..Default::default()
+        })
+        .insert(ScoreBoard)
+        .insert(player);
+}
+
+fn update_scoreboard(
+    score: Res<Score>,
+    mut query: Query<(&mut Text, &Player), With<ScoreBoard>>,
+) {
     if !score.is_changed() {
         return;
     }
 
-    for mut text in query.iter_mut() {
-        text.sections[0].value = format!("{}", **score)
+    for (mut text, player) in query.iter_mut() {
+        text.sections[0].value = format!("{}", score.of(player))
     }
 }
 
-fn add_points_for_eaten_dot(mut score: ResMut<Score>, mut event_reader: EventReader<EDotEaten>) {
-    for _ in event_reader.iter() {
-        score.add(POINTS_PER_DOT)
+fn add_points_for_eaten_dot(
+    mut score: ResMut<Score>,
+    mut event_reader: EventReader<EDotEaten>,
+    capman_query: Query<&Player>,
+) {
+    for event in event_reader.iter() {
+        if let Ok(player) = capman_query.get(**event) {
+            score.add(*player, POINTS_PER_DOT)
+        }
     }
 }
 
 fn add_points_for_eaten_energizer(
     mut score: ResMut<Score>,
     mut event_reader: EventReader<EEnergizerEaten>,
+    capman_query: Query<&Player>,
 ) {
-    for _ in event_reader.iter() {
-        score.add(POINTS_PER_ENERGIZER)
+    for event in event_reader.iter() {
+        if let Ok(player) = capman_query.get(**event) {
+            score.add(*player, POINTS_PER_ENERGIZER)
+        }
     }
 }
 
@@ -131,10 +198,15 @@ fn add_points_for_eaten_ghost_and_display_score_text(
     mut score: ResMut<Score>,
     mut eaten_ghost_counter: ResMut<EatenGhostCounter>,
     mut event_reader: EventReader<EGhostEaten>,
+    capman_query: Query<&Player>,
 ) {
     for event in event_reader.iter() {
+        let player = match capman_query.get(event.2) {
+            Ok(player) => *player,
+            Err(_) => continue,
+        };
         let points = POINTS_PER_GHOST * 2usize.pow(**eaten_ghost_counter as u32);
-        score.add(points);
+        score.add(player, points);
         **eaten_ghost_counter += 1;
 
         let mut coordinates = event.1.translation;
@@ -163,9 +235,14 @@ fn add_points_for_eaten_fruit_and_display_score_text(
     game_asset_handles: Res<LoadedAssets>,
     mut score: ResMut<Score>,
     mut event_reader: EventReader<EFruitEaten>,
+    capman_query: Query<&Player>,
 ) {
     for event in event_reader.iter() {
         let (fruit, transform) = (event.0, event.1);
+        let player = match capman_query.get(event.2) {
+            Ok(player) => *player,
+            Err(_) => continue,
+        };
 
         let points = match fruit {
             Cherry => 100,
@@ -181,7 +258,7 @@ fn add_points_for_eaten_fruit_and_display_score_text(
         let mut coordinates = transform.translation;
         coordinates.z = TEXT_Z;
 
-        score.add(points);
+        score.add(player, points);
         spawn_score_text(
             &mut commands,
             &game_asset_handles,
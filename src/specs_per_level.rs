@@ -1,3 +1,4 @@
+use crate::difficulty::Difficulty;
 use crate::edibles::fruit::Fruit;
 use crate::edibles::fruit::Fruit::*;
 use crate::level::Level;
@@ -8,10 +9,15 @@ pub struct SpecsPerLevelPlugin;
 
 impl Plugin for SpecsPerLevelPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(create_specs_per_level());
+        app.insert_resource(create_specs_per_level())
+            .add_startup_system(scale_specs_by_difficulty);
     }
 }
 
+fn scale_specs_by_difficulty(difficulty: Res<Difficulty>, mut specs_per_level: ResMut<SpecsPerLevel>) {
+    specs_per_level.scale_by(&difficulty)
+}
+
 fn create_specs_per_level() -> SpecsPerLevel {
     SpecsPerLevel::from_levels_and_specs(
         [
@@ -29,6 +35,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     elroy_2_dots_left: 10,
                     elroy_2_speed_modifier: 0.85,
                     frightened_time: 6.0,
+                    frightened_flashes: 5,
+                    ghost_house_dot_limits: vec![0, 0, 30, 60],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 4.0,
                 },
             ),
             (
@@ -45,6 +55,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     elroy_2_dots_left: 15,
                     elroy_2_speed_modifier: 0.95,
                     frightened_time: 5.0,
+                    frightened_flashes: 5,
+                    ghost_house_dot_limits: vec![0, 0, 0, 50],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 4.0,
                 },
             ),
             (
@@ -61,6 +75,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     elroy_2_dots_left: 20,
                     elroy_2_speed_modifier: 0.95,
                     frightened_time: 4.0,
+                    frightened_flashes: 5,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 4.0,
                 },
             ),
             (
@@ -77,6 +95,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 0.95,
                     ghost_frightened_speed_modifier: 0.55,
                     frightened_time: 3.0,
+                    frightened_flashes: 5,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 4.0,
                 },
             ),
             (
@@ -93,6 +115,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 2.0,
+                    frightened_flashes: 5,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -109,6 +135,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 5.0,
+                    frightened_flashes: 5,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -125,6 +155,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 2.0,
+                    frightened_flashes: 5,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -141,6 +175,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 2.0,
+                    frightened_flashes: 5,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -157,6 +195,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 1.0,
+                    frightened_flashes: 3,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -173,6 +215,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 5.0,
+                    frightened_flashes: 5,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -189,6 +235,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 2.0,
+                    frightened_flashes: 5,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -205,6 +255,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 1.0,
+                    frightened_flashes: 3,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -221,6 +275,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 1.0,
+                    frightened_flashes: 3,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -237,6 +295,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 3.0,
+                    frightened_flashes: 5,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -253,6 +315,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 1.0,
+                    frightened_flashes: 3,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -269,6 +335,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 1.0,
+                    frightened_flashes: 3,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -285,6 +355,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.95,
                     frightened_time: 0.0,
+                    frightened_flashes: 0,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -301,6 +375,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.6,
                     frightened_time: 1.0,
+                    frightened_flashes: 3,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -317,6 +395,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.95,
                     frightened_time: 0.0,
+                    frightened_flashes: 0,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
             (
@@ -333,6 +415,10 @@ fn create_specs_per_level() -> SpecsPerLevel {
                     capman_frightened_speed_modifier: 1.0,
                     ghost_frightened_speed_modifier: 0.95,
                     frightened_time: 0.0,
+                    frightened_flashes: 0,
+                    ghost_house_dot_limits: vec![0, 0, 0, 0],
+                    ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+                    ghost_house_release_time: 3.0,
                 },
             ),
         ],
@@ -348,10 +434,22 @@ fn create_specs_per_level() -> SpecsPerLevel {
             elroy_2_dots_left: 60,
             elroy_2_speed_modifier: 1.05,
             frightened_time: 0.0,
+            frightened_flashes: 0,
+            ghost_house_dot_limits: vec![0, 0, 0, 0],
+            ghost_house_global_dot_limits: vec![0, 7, 17, 32],
+            ghost_house_release_time: 3.0,
         },
     )
 }
 
+#[cfg(test)]
+impl SpecsPerLevel {
+    /// The specs of the arcade game, unscaled by any difficulty.
+    pub(crate) fn arcade() -> Self {
+        create_specs_per_level()
+    }
+}
+
 pub struct SpecsPerLevel {
     level_to_spec: HashMap<Level, Spec>,
     default: Spec,
@@ -374,9 +472,20 @@ impl SpecsPerLevel {
     pub fn get_for(&self, level: &Level) -> &Spec {
         self.level_to_spec.get(level).unwrap_or(&self.default)
     }
+
+    /// Replace the spec for the given level, for example to adjust the difficulty while the game is running.
+    pub fn set_for(&mut self, level: Level, spec: Spec) {
+        self.level_to_spec.insert(level, spec);
+    }
+
+    fn scale_by(&mut self, difficulty: &Difficulty) {
+        for spec in self.level_to_spec.values_mut().chain([&mut self.default]) {
+            *spec = spec.scaled_by(difficulty)
+        }
+    }
 }
 
-#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Spec {
     pub fruit_to_spawn: Fruit,
     pub capman_normal_speed_modifier: f32,
@@ -388,14 +497,48 @@ pub struct Spec {
     pub elroy_1_speed_modifier: f32,
     pub elroy_2_dots_left: usize,
     pub elroy_2_speed_modifier: f32,
+    /// Seconds ghosts stay frightened. With zero, ghosts only reverse when an energizer gets eaten.
     pub frightened_time: f32,
+    /// How often frightened ghosts flash before they recover.
+    pub frightened_flashes: usize,
+    /// Dots the ghosts of a house have to wait for, in release order (see GhostHouseGate). The list can have
+    /// any length: ghosts beyond its end wait for its last limit, and without any limit no ghost waits for dots.
+    pub ghost_house_dot_limits: Vec<usize>,
+    /// The limits of the global counter, which is active after a lost life. Same order and fallback as the per
+    /// ghost limits.
+    pub ghost_house_global_dot_limits: Vec<usize>,
+    /// Seconds after which the ghost house releases the waiting ghost if capman stops eating dots.
+    pub ghost_house_release_time: f32,
+}
+
+impl Spec {
+    /// Return this spec with the ghost speeds, frightened time, elroy thresholds and ghost house release time
+    /// scaled by the given difficulty.
+    fn scaled_by(&self, difficulty: &Difficulty) -> Spec {
+        let ghost_speed = difficulty.ghost_speed_factor();
+        let elroy_dots = |dots: usize| (dots as f32 * difficulty.elroy_dots_factor()).round() as usize;
+
+        Spec {
+            ghost_normal_speed_modifier: self.ghost_normal_speed_modifier * ghost_speed,
+            ghost_tunnel_speed_modifier: self.ghost_tunnel_speed_modifier * ghost_speed,
+            ghost_frightened_speed_modifier: self.ghost_frightened_speed_modifier * ghost_speed,
+            elroy_1_dots_left: elroy_dots(self.elroy_1_dots_left),
+            elroy_1_speed_modifier: self.elroy_1_speed_modifier * ghost_speed,
+            elroy_2_dots_left: elroy_dots(self.elroy_2_dots_left),
+            elroy_2_speed_modifier: self.elroy_2_speed_modifier * ghost_speed,
+            frightened_time: self.frightened_time * difficulty.frightened_time_factor(),
+            ghost_house_release_time: self.ghost_house_release_time * difficulty.release_timer_factor(),
+            ..self.clone()
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::difficulty::Difficulty;
     use crate::edibles::fruit::Fruit;
     use crate::level::Level;
-    use crate::specs_per_level::{Spec, SpecsPerLevel};
+    use crate::specs_per_level::{create_specs_per_level, Spec, SpecsPerLevel};
 
     #[test]
     fn it_can_be_created_from_an_iterator_of_usize_spec_tuples() {
@@ -415,6 +558,10 @@ mod tests {
                         elroy_2_dots_left: 1,
                         elroy_2_speed_modifier: 1.0,
                         frightened_time: 1.0,
+                        frightened_flashes: 1,
+                        ghost_house_dot_limits: vec![1; 4],
+                        ghost_house_global_dot_limits: vec![1; 4],
+                        ghost_house_release_time: 1.0,
                     },
                 ),
                 (
@@ -431,6 +578,10 @@ mod tests {
                         elroy_2_dots_left: 2,
                         elroy_2_speed_modifier: 2.0,
                         frightened_time: 2.0,
+                        frightened_flashes: 2,
+                        ghost_house_dot_limits: vec![2; 4],
+                        ghost_house_global_dot_limits: vec![2; 4],
+                        ghost_house_release_time: 2.0,
                     },
                 ),
             ],
@@ -467,6 +618,10 @@ mod tests {
             elroy_2_dots_left: 1,
             elroy_2_speed_modifier: 1.0,
             frightened_time: 1.0,
+            frightened_flashes: 1,
+            ghost_house_dot_limits: vec![1; 4],
+            ghost_house_global_dot_limits: vec![1; 4],
+            ghost_house_release_time: 1.0,
         };
 
         let specs_per_level =
@@ -476,4 +631,25 @@ mod tests {
 
         assert_eq!(retrieved_spec, &specs_per_level.default)
     }
+
+    #[test]
+    fn the_arcade_difficulty_keeps_the_spec_unchanged() {
+        let spec = create_specs_per_level().get_for(&Level(1)).clone();
+
+        assert_eq!(spec.scaled_by(&Difficulty::Arcade), spec)
+    }
+
+    #[test]
+    fn harder_difficulties_make_ghosts_faster_and_shorten_the_frightened_time() {
+        let spec = create_specs_per_level().get_for(&Level(1)).clone();
+        let easy = spec.scaled_by(&Difficulty::Easy);
+        let nightmare = spec.scaled_by(&Difficulty::Nightmare);
+
+        assert!(easy.ghost_normal_speed_modifier < spec.ghost_normal_speed_modifier);
+        assert!(nightmare.ghost_normal_speed_modifier > spec.ghost_normal_speed_modifier);
+        assert!(easy.frightened_time > nightmare.frightened_time);
+        assert!(easy.elroy_1_dots_left < nightmare.elroy_1_dots_left);
+        assert!(easy.ghost_house_release_time > nightmare.ghost_house_release_time);
+        assert_eq!(nightmare.capman_normal_speed_modifier, spec.capman_normal_speed_modifier);
+    }
 }
@@ -3,11 +3,12 @@ use bevy::ecs::query::WorldQuery;
 use bevy::prelude::*;
 
 use crate::capman::Capman;
-use crate::edibles::dots::EatenDots;
-use crate::edibles::energizer::EnergizerTimer;
+use crate::edibles::energizer::{EnergizerTimer, LEnergizerTimer};
+use crate::ghosts::elroy::Elroy;
+use crate::ghosts::movement::LGhostMovement;
 use crate::ghosts::state::State;
+use crate::ghosts::target::LTargetSetter;
 use crate::ghosts::Ghost;
-use crate::ghosts::Ghost::*;
 use crate::level::Level;
 use crate::life_cycle::LifeCycle;
 use crate::map::board::Board;
@@ -19,109 +20,77 @@ impl Plugin for SpeedPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(
             SystemSet::on_update(LifeCycle::Running)
-                .with_system(update_ghost_speed)
-                .with_system(update_capman_speed),
+                .with_system(
+                    update_ghost_speed
+                        .after(LTargetSetter)
+                        .before(LGhostMovement),
+                )
+                .with_system(update_capman_speed.after(LEnergizerTimer)),
         );
     }
 }
 
 /// The current speed of a moving entity
-#[derive(Copy, Clone, Component, Deref, DerefMut)]
+#[derive(Copy, Clone, Component, Debug, Deref, DerefMut)]
 pub struct Speed(pub f32);
 
 #[derive(WorldQuery)]
 #[world_query(mutable)]
 struct GhostSpeedUpdateComponents<'a> {
-    ghost: &'a Ghost,
     transform: &'a Transform,
     speed: &'a mut Speed,
     state: &'a State,
+    elroy: Option<&'a Elroy>,
 }
 
+/// Ghosts with elroy (like blinky) get two speed bonuses, depending on their elroy level.
+///
+/// Speed zones on the map are applied on top, but not to eaten ghosts.
 fn update_ghost_speed(
     board: Res<Board>,
     level: Res<Level>,
-    eaten_dots: Res<EatenDots>,
     specs_per_level: Res<SpecsPerLevel>,
     dimensions: Res<BoardDimensions>,
-    mut query: Query<GhostSpeedUpdateComponents>,
-) {
-    for mut comps in query.iter_mut() {
-        match *comps.ghost {
-            Blinky => update_blinky_speed(
-                &board,
-                &level,
-                &specs_per_level,
-                &dimensions,
-                &eaten_dots,
-                &mut comps,
-            ),
-            _ => update_non_blinky_speed(&board, &level, &specs_per_level, &dimensions, &mut comps),
-        }
-    }
-}
-
-/// Blinkys speed is set differently, as he has the elroy mode. He
-/// gets two speed bonuses, depending on the remaining dots on the board.
-/// The amount of dots to trigger elroy depends on the current level.
-fn update_blinky_speed(
-    board: &Board,
-    level: &Level,
-    specs_per_level: &SpecsPerLevel,
-    dimensions: &BoardDimensions,
-    eaten_dots: &EatenDots,
-    comps: &mut GhostSpeedUpdateComponentsItem,
+    mut query: Query<GhostSpeedUpdateComponents, With<Ghost>>,
 ) {
     let ghost_speed = dimensions.ghost_base_speed();
     let spec = specs_per_level.get_for(&level);
-    let remaining_dots = eaten_dots.get_remaining();
 
-    if *comps.state == State::Eaten {
-        *comps.speed = Speed(ghost_speed * 2.0)
-    } else if board.position_is_tunnel(&dimensions.trans_to_pos(comps.transform)) {
-        *comps.speed = Speed(ghost_speed * spec.ghost_tunnel_speed_modifier);
-    } else if *comps.state == State::Frightened {
-        *comps.speed = Speed(ghost_speed * spec.ghost_frightened_speed_modifier)
-    } else if remaining_dots <= spec.elroy_2_dots_left {
-        *comps.speed = Speed(ghost_speed * spec.elroy_2_speed_modifier)
-    } else if remaining_dots <= spec.elroy_1_dots_left {
-        *comps.speed = Speed(ghost_speed * spec.elroy_1_speed_modifier)
-    } else {
-        *comps.speed = Speed(ghost_speed * spec.ghost_normal_speed_modifier)
-    }
-}
+    for mut comps in query.iter_mut() {
+        let elroy_level = comps.elroy.map(Elroy::active_level).unwrap_or(0);
+        let position = dimensions.trans_to_pos(comps.transform);
 
-fn update_non_blinky_speed(
-    board: &Board,
-    level: &Level,
-    specs_per_level: &SpecsPerLevel,
-    dimensions: &BoardDimensions,
-    comps: &mut GhostSpeedUpdateComponentsItem,
-) {
-    let ghost_speed = dimensions.ghost_base_speed();
-    let spec = specs_per_level.get_for(&level);
+        if *comps.state == State::Eaten {
+            *comps.speed = Speed(ghost_speed * 2.0)
+        } else if board.position_is_tunnel(&position) {
+            *comps.speed = Speed(ghost_speed * spec.ghost_tunnel_speed_modifier);
+        } else if *comps.state == State::Frightened {
+            *comps.speed = Speed(ghost_speed * spec.ghost_frightened_speed_modifier)
+        } else if elroy_level == 2 {
+            *comps.speed = Speed(ghost_speed * spec.elroy_2_speed_modifier)
+        } else if elroy_level == 1 {
+            *comps.speed = Speed(ghost_speed * spec.elroy_1_speed_modifier)
+        } else {
+            *comps.speed = Speed(ghost_speed * spec.ghost_normal_speed_modifier)
+        }
 
-    if *comps.state == State::Eaten {
-        *comps.speed = Speed(ghost_speed * 2.0)
-    } else if board.position_is_tunnel(&dimensions.trans_to_pos(comps.transform)) {
-        *comps.speed = Speed(ghost_speed * spec.ghost_tunnel_speed_modifier);
-    } else if *comps.state == State::Frightened {
-        *comps.speed = Speed(ghost_speed * spec.ghost_frightened_speed_modifier)
-    } else {
-        *comps.speed = Speed(ghost_speed * spec.ghost_normal_speed_modifier)
+        if *comps.state != State::Eaten {
+            **comps.speed *= board.ghost_speed_modifier_at(&position)
+        }
     }
 }
 
 fn update_capman_speed(
+    board: Res<Board>,
     level: Res<Level>,
     specs_per_level: Res<SpecsPerLevel>,
     energizer_timer: Option<Res<EnergizerTimer>>,
     dimensions: Res<BoardDimensions>,
-    mut query: Query<&mut Speed, With<Capman>>,
+    mut query: Query<(&Transform, &mut Speed), With<Capman>>,
 ) {
     let capman_speed = dimensions.capman_base_speed();
 
-    for mut speed in query.iter_mut() {
+    for (transform, mut speed) in query.iter_mut() {
         let spec = specs_per_level.get_for(&level);
 
         if energizer_timer.is_some() {
@@ -129,5 +98,7 @@ fn update_capman_speed(
         } else {
             *speed = Speed(capman_speed * spec.capman_normal_speed_modifier);
         }
+
+        **speed *= board.capman_speed_modifier_at(&dimensions.trans_to_pos(transform));
     }
 }
@@ -1,3 +1,6 @@
+use crate::capman::LCapmanMovement;
+use crate::ghosts::movement::LGhostMovement;
+use crate::interactions::LCapmanInteractions;
 use crate::life_cycle::LifeCycle::*;
 use bevy::prelude::*;
 
@@ -15,8 +18,12 @@ impl Plugin for TunnelPlugin {
             .add_system_set(SystemSet::on_enter(Start).with_system(spawn_tunnels))
             .add_system_set(
                 SystemSet::on_update(Running)
-                    .with_system(move_capman_through_tunnel)
-                    .with_system(move_ghost_trough_tunnel),
+                    .with_system(
+                        move_capman_through_tunnel
+                            .after(LCapmanMovement)
+                            .before(LCapmanInteractions),
+                    )
+                    .with_system(move_ghost_trough_tunnel.after(LGhostMovement)),
             );
     }
 }